@@ -2,8 +2,11 @@
 
 pub mod abel;
 pub mod fermat;
+pub mod fourier;
 pub mod galois;
 pub mod gauss;
+pub mod lagrange;
+pub mod weierstrass;
 
 pub use abel::{AbelianGroup, AbelianMonoid, Inv, Modulus, UnitGroup};
 pub use fermat::PrimeModulus;
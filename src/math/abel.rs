@@ -21,9 +21,9 @@ pub trait AbelianMonoid:
     + Send
     + Sized
     + Sync
-    + Mul<Self>
+    + Mul<Self, Output = Self>
     + MulAssign<Self>
-    + for<'a> Mul<&'a Self>
+    + for<'a> Mul<&'a Self, Output = Self>
     + for<'a> MulAssign<&'a Self>
 {
     type Uint;
@@ -84,7 +84,7 @@ macro_rules! impl_make_new_unitgroup_elem {
         impl Modulus<$limbs> {
             /// constructs a number in UnitGroup of this modulus
             pub fn make(&self, value: &Uint<$limbs>) -> Option<UnitGroup<$limbs>> {
-                if self.0.modulus().gcd(value).unwrap() == Uint::<$limbs>::ONE {
+                if self.0.modulus().gcd(value) == Uint::<$limbs>::ONE {
                     Some(UnitGroup(MontyForm::new(value, self.0)))
                 } else {
                     None
@@ -0,0 +1,287 @@
+//! number-theoretic transform over prime fields
+//! Speeding up the Number Theoretic Transform for Faster Ideal Lattice-Based Cryptography
+//! https://eprint.iacr.org/2016/504.pdf
+//! Polynomials of `Z_p[X] / (X^n + 1)` are multiplied pointwise after the
+//! negacyclic transform, which needs a prime `p = 1 mod 2n` and `n` a power of two.
+
+use super::{
+    abel::{AbelianMonoid, Inv},
+    fermat::PrimeModulus,
+    galois::primefield::PrimeField,
+    gauss::{Odd, Uint},
+};
+
+/// finds the `count` largest primes below `2^bits` that are `1 mod 2n`
+pub fn ntt_primes(bits: u32, n: usize, count: usize) -> Vec<PrimeModulus<1>> {
+    assert!(bits <= 63 && n.is_power_of_two());
+    let step = 2 * n as u64;
+    let mut primes = Vec::with_capacity(count);
+    let mut candidate = ((1u64 << bits) - 2) / step * step + 1;
+    while primes.len() < count {
+        assert!(candidate > step, "too few primes of {bits} bits");
+        if let Ok(prime) = PrimeModulus::<1>::try_from(Uint::from_u64(candidate).to_odd().unwrap())
+        {
+            primes.push(prime);
+        }
+        candidate -= step;
+    }
+    primes
+}
+
+/// gets the integer of a field element
+pub fn value(x: &PrimeField<1>) -> u64 {
+    x.rep().as_words()[0]
+}
+
+/// gets the prime of a modulus
+pub fn prime(modulus: &PrimeModulus<1>) -> u64 {
+    Odd::from(*modulus).get().as_words()[0]
+}
+
+/// reverses the lowest `bits` bits
+fn reverse(i: usize, bits: u32) -> usize {
+    i.reverse_bits() >> (usize::BITS - bits)
+}
+
+/// negacyclic transform of length n modulo a prime
+#[derive(Debug, Clone)]
+pub struct Transform {
+    modulus: PrimeModulus<1>,
+    /// powers of a primitive 2n-th root of unity in bit-reversed order
+    roots: Vec<PrimeField<1>>,
+    /// powers of its inverse in bit-reversed order
+    inverses: Vec<PrimeField<1>>,
+    /// inverse of n
+    scale: PrimeField<1>,
+}
+
+impl Transform {
+    pub fn new(modulus: PrimeModulus<1>, n: usize) -> Self {
+        let one = modulus.make(&Uint::ONE);
+        let order = prime(&modulus);
+        assert!(
+            n.is_power_of_two() && (order - 1).is_multiple_of(2 * n as u64),
+            "the modulus must be 1 mod 2n for n a power of two"
+        );
+        // a random element to the power of (p - 1) / 2n is a primitive 2n-th root if its n-th power is -1
+        let exponent = Uint::from_u64((order - 1) / (2 * n as u64));
+        let psi = loop {
+            let psi = modulus.random_make().pow(&exponent);
+            if psi.pow(&Uint::from_u64(n as u64)) == -one {
+                break psi;
+            }
+        };
+        let bits = n.trailing_zeros();
+        let powers = |root: PrimeField<1>| {
+            let mut powers = vec![one; n];
+            for i in 1..n {
+                powers[i] = powers[i - 1] * root;
+            }
+            (0..n).map(|i| powers[reverse(i, bits)]).collect()
+        };
+        Self {
+            modulus,
+            roots: powers(psi),
+            inverses: powers(psi.inv()),
+            scale: modulus.make(&Uint::from_u64(n as u64)).inv(),
+        }
+    }
+
+    pub fn modulus(&self) -> PrimeModulus<1> {
+        self.modulus
+    }
+
+    /// length of the transform
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// transforms coefficients into evaluations in place, in bit-reversed order
+    pub fn forward(&self, a: &mut [PrimeField<1>]) {
+        let n = self.len();
+        assert_eq!(n, a.len());
+        let (mut t, mut m) = (n, 1);
+        while m < n {
+            t /= 2;
+            for i in 0..m {
+                let root = self.roots[m + i];
+                for j in 2 * i * t..2 * i * t + t {
+                    let (u, v) = (a[j], a[j + t] * root);
+                    a[j] = u + v;
+                    a[j + t] = u - v;
+                }
+            }
+            m *= 2;
+        }
+    }
+
+    /// transforms evaluations back into coefficients in place
+    pub fn inverse(&self, a: &mut [PrimeField<1>]) {
+        let n = self.len();
+        assert_eq!(n, a.len());
+        let (mut t, mut m) = (1, n);
+        while m > 1 {
+            let h = m / 2;
+            for i in 0..h {
+                let root = self.inverses[h + i];
+                for j in 2 * i * t..2 * i * t + t {
+                    let (u, v) = (a[j], a[j + t]);
+                    a[j] = u + v;
+                    a[j + t] = (u - v) * root;
+                }
+            }
+            t *= 2;
+            m = h;
+        }
+        for x in a.iter_mut() {
+            *x *= self.scale;
+        }
+    }
+
+    /// multiplies two polynomials modulo `X^n + 1`
+    pub fn multiply(&self, a: &[PrimeField<1>], b: &[PrimeField<1>]) -> Vec<PrimeField<1>> {
+        let (mut a, mut b) = (a.to_vec(), b.to_vec());
+        self.forward(&mut a);
+        self.forward(&mut b);
+        for (a, b) in a.iter_mut().zip(&b) {
+            *a *= b;
+        }
+        self.inverse(&mut a);
+        a
+    }
+}
+
+/// multiplies polynomials of `Z[X] / (X^n + 1)` exactly, by transforms modulo
+/// three primes of 62 bits and the Chinese remainder theorem
+#[derive(Debug, Clone)]
+pub struct ExactMultiplier {
+    transforms: [Transform; 3],
+    /// `p1^-1 mod p2`, `p1^-1 mod p3` and `p2^-1 mod p3`
+    inverses: [PrimeField<1>; 3],
+}
+
+impl ExactMultiplier {
+    pub fn new(n: usize) -> Self {
+        let primes = ntt_primes(62, n, 3);
+        let inverse =
+            |a: &PrimeModulus<1>, b: &PrimeModulus<1>| b.make(&Uint::from_u64(prime(a))).inv();
+        Self {
+            inverses: [
+                inverse(&primes[0], &primes[1]),
+                inverse(&primes[0], &primes[2]),
+                inverse(&primes[1], &primes[2]),
+            ],
+            transforms: std::array::from_fn(|i| Transform::new(primes[i], n)),
+        }
+    }
+
+    /// multiplies polynomials whose product has coefficients less than `2^126` in magnitude
+    pub fn multiply(&self, a: &[i64], b: &[i64]) -> Vec<i128> {
+        let residues: Vec<Vec<u64>> = self
+            .transforms
+            .iter()
+            .map(|transform| {
+                let modulus = transform.modulus();
+                let embed = |a: &[i64]| -> Vec<PrimeField<1>> {
+                    a.iter()
+                        .map(|&x| {
+                            let y = modulus.make(&Uint::from_u64(x.unsigned_abs()));
+                            if x < 0 {
+                                -y
+                            } else {
+                                y
+                            }
+                        })
+                        .collect()
+                };
+                transform
+                    .multiply(&embed(a), &embed(b))
+                    .iter()
+                    .map(value)
+                    .collect()
+            })
+            .collect();
+        let primes: Vec<u64> = self
+            .transforms
+            .iter()
+            .map(|transform| prime(&transform.modulus()))
+            .collect();
+        let moduli = self.transforms.each_ref().map(Transform::modulus);
+        (0..a.len())
+            .map(|i| {
+                // mixed-radix digits of Garner's algorithm
+                let (r1, r2, r3) = (residues[0][i], residues[1][i], residues[2][i]);
+                let k2 = (moduli[1].make(&Uint::from_u64(r2))
+                    - moduli[1].make(&Uint::from_u64(r1)))
+                    * self.inverses[0];
+                let k3 = ((moduli[2].make(&Uint::from_u64(r3))
+                    - moduli[2].make(&Uint::from_u64(r1)))
+                    * self.inverses[1]
+                    - moduli[2].make(&Uint::from_u64(value(&k2))))
+                    * self.inverses[2];
+                let (k2, k3) = (value(&k2) as u128, value(&k3) as u128);
+                let (p1, p2, p3) = (primes[0] as u128, primes[1] as u128, primes[2] as u128);
+                let x = (r1 as u128)
+                    .wrapping_add(p1.wrapping_mul(k2))
+                    .wrapping_add(p1.wrapping_mul(p2).wrapping_mul(k3));
+                // a large top digit means a negative value, since the product of the primes exceeds 2^129
+                if k3 > p3 / 2 {
+                    x.wrapping_sub(p1.wrapping_mul(p2).wrapping_mul(p3)) as i128
+                } else {
+                    x as i128
+                }
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_correctness() {
+    let n = 64;
+    let primes = ntt_primes(40, n, 2);
+    assert!(primes
+        .iter()
+        .map(prime)
+        .all(|p| p < 1 << 40 && p % 128 == 1));
+    let transform = Transform::new(primes[0], n);
+    let modulus = primes[0];
+    let a: Vec<_> = (0..n).map(|_| modulus.random_make()).collect();
+    let b: Vec<_> = (0..n).map(|_| modulus.random_make()).collect();
+    let mut c = a.clone();
+    transform.forward(&mut c);
+    transform.inverse(&mut c);
+    assert_eq!(a, c);
+    // schoolbook multiplication modulo X^n + 1
+    let mut expected = vec![modulus.make(&Uint::ZERO); n];
+    for i in 0..n {
+        for j in 0..n {
+            let product = a[i] * b[j];
+            if i + j < n {
+                expected[i + j] += product;
+            } else {
+                expected[i + j - n] -= product;
+            }
+        }
+    }
+    assert_eq!(expected, transform.multiply(&a, &b));
+
+    let multiplier = ExactMultiplier::new(n);
+    let a: Vec<i64> = (0..n as i64).map(|i| (i - 20) << 56).collect();
+    let b: Vec<i64> = (0..n as i64).map(|i| (7 - i) << 50).collect();
+    let mut expected = vec![0i128; n];
+    for i in 0..n {
+        for j in 0..n {
+            let product = a[i] as i128 * b[j] as i128;
+            if i + j < n {
+                expected[i + j] += product;
+            } else {
+                expected[i + j - n] -= product;
+            }
+        }
+    }
+    assert_eq!(expected, multiplier.multiply(&a, &b));
+}
@@ -1,24 +1,70 @@
 //! finite field arithmetic
 
 use super::abel::AbelianGroup;
+use super::gauss::{Uint, Word};
+use std::fmt::Debug;
 use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
 /// provides functionality to finite field
 pub trait FiniteField:
     AbelianGroup
-    + Neg
-    + Add<Self>
-    + Sub<Self>
+    + Debug
+    + Neg<Output = Self>
+    + Add<Self, Output = Self>
+    + Sub<Self, Output = Self>
     + AddAssign<Self>
     + SubAssign<Self>
-    + for<'a> Add<&'a Self>
-    + for<'a> Sub<&'a Self>
+    + for<'a> Add<&'a Self, Output = Self>
+    + for<'a> Sub<&'a Self, Output = Self>
     + for<'a> AddAssign<&'a Self>
     + for<'a> SubAssign<&'a Self>
 {
     fn zero(&self) -> Self;
     fn random(&self) -> Self;
     fn char(&self) -> Self::Uint;
+    /// embeds an integer into this field, i.e., `value` in a prime field
+    /// or the polynomial with coefficients `value` in a binary field
+    fn embed(&self, value: u64) -> Self;
+    /// encodes this element into little-endian bytes of a fixed length
+    fn encode(&self) -> Vec<u8>;
+    /// decodes an element of the same field from bytes
+    fn decode(&self, bytes: &[u8]) -> Option<Self>;
+}
+
+/// encodes an integer into little-endian bytes
+pub(crate) fn encode_uint<const LIMBS: usize>(value: &Uint<LIMBS>) -> Vec<u8> {
+    value
+        .as_words()
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect()
+}
+
+/// decodes an integer from little-endian bytes of exact length
+pub(crate) fn decode_uint<const LIMBS: usize>(bytes: &[u8]) -> Option<Uint<LIMBS>> {
+    const WORD: usize = std::mem::size_of::<Word>();
+    if bytes.len() != LIMBS * WORD {
+        return None;
+    }
+    let mut words = [0 as Word; LIMBS];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks(WORD)) {
+        *word = Word::from_le_bytes(chunk.try_into().ok()?);
+    }
+    Some(Uint::from_words(words))
+}
+
+/// encodes a sequence of field elements
+pub fn encode_all<F: FiniteField>(elems: &[F]) -> Vec<u8> {
+    elems.iter().flat_map(|elem| elem.encode()).collect()
+}
+
+/// decodes a sequence of elements of the same field as `field`
+pub fn decode_all<F: FiniteField>(field: &F, bytes: &[u8]) -> Option<Vec<F>> {
+    let size = field.encode().len();
+    if !bytes.len().is_multiple_of(size) {
+        return None;
+    }
+    bytes.chunks(size).map(|chunk| field.decode(chunk)).collect()
 }
 
 pub mod binaryfield;
@@ -2,7 +2,7 @@
 
 use crate::math::{
     abel::{AbelianMonoid, Inv},
-    galois::{irreducible::BinaryIrreducible, FiniteField},
+    galois::{decode_uint, encode_uint, irreducible::BinaryIrreducible, FiniteField},
     gauss::{rand_core::OsRng, Integer, RandomBits, Uint, Zero},
 };
 use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
@@ -248,4 +248,21 @@ impl<const EXP: u32, const LIMBS: usize> FiniteField for BinaryField<EXP, LIMBS>
     fn char(&self) -> Self::Uint {
         Uint::<LIMBS>::from_u8(2)
     }
+
+    fn embed(&self, value: u64) -> Self {
+        Self(Uint::<LIMBS>::from_u64(value) & Self::MASK)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        encode_uint(&self.0)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<Self> {
+        let value = decode_uint::<LIMBS>(bytes)?;
+        if value & Self::MASK == value {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
 }
@@ -3,8 +3,8 @@
 use crate::math::{
     abel::{Inv, UnitGroup},
     fermat::PrimeModulus,
-    galois::FiniteField,
-    gauss::modular::MontyForm,
+    galois::{decode_uint, encode_uint, FiniteField},
+    gauss::{modular::MontyForm, Uint},
 };
 use std::ops::{Add, AddAssign, Div, DivAssign, Neg, Sub, SubAssign};
 
@@ -88,6 +88,23 @@ macro_rules! impl_trait_fns {
             fn char(&self) -> Self::Uint {
                 self.0.params().modulus().get()
             }
+
+            fn embed(&self, value: u64) -> Self {
+                Self(MontyForm::new(&Uint::from_u64(value), *self.0.params()))
+            }
+
+            fn encode(&self) -> Vec<u8> {
+                encode_uint(&self.0.retrieve())
+            }
+
+            fn decode(&self, bytes: &[u8]) -> Option<Self> {
+                let value = decode_uint::<$limbs>(bytes)?;
+                if value < self.modulus() {
+                    Some(Self(MontyForm::new(&value, *self.0.params())))
+                } else {
+                    None
+                }
+            }
         }
 
         crate::math::abel::impl_div_as_mulinv!(PrimeField<$limbs>);
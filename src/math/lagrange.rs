@@ -0,0 +1,45 @@
+//! polynomial arithmetic and Lagrange interpolation over finite fields
+
+use super::galois::FiniteField;
+
+/// samples a random polynomial of given degree with fixed constant term
+/// Coefficients are stored from the lowest degree to the highest.
+pub fn random<F: FiniteField>(constant: F, degree: usize) -> Vec<F> {
+    let mut coeffs = Vec::with_capacity(degree + 1);
+    coeffs.push(constant);
+    coeffs.extend((0..degree).map(|_| constant.random()));
+    coeffs
+}
+
+/// evaluates the polynomial at `x` by Horner's rule
+pub fn evaluate<F: FiniteField>(coeffs: &[F], x: &F) -> F {
+    coeffs
+        .iter()
+        .rev()
+        .fold(x.zero(), |acc, coeff| acc * x + coeff)
+}
+
+/// computes the Lagrange basis at `at` for distinct points `xs`
+pub fn basis<F: FiniteField>(xs: &[F], at: &F) -> Vec<F> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            let (num, den) = xs
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold((at.one(), at.one()), |(num, den), (_, xj)| {
+                    (num * (*at - xj), den * (*xi - xj))
+                });
+            num * den.inv()
+        })
+        .collect()
+}
+
+/// interpolates the polynomial through `(xs[i], ys[i])` and evaluates it at `at`
+pub fn interpolate<F: FiniteField>(xs: &[F], ys: &[F], at: &F) -> F {
+    basis(xs, at)
+        .into_iter()
+        .zip(ys)
+        .fold(at.zero(), |acc, (l, y)| acc + l * y)
+}
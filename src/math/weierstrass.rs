@@ -0,0 +1,126 @@
+//! elliptic curve utilities on secp256k1
+//! Currently use k256 as backend, whose scalars also form a FiniteField
+
+use super::{
+    abel::{AbelianMonoid, Inv},
+    galois::FiniteField,
+    gauss::Uint,
+};
+use blake2::{Blake2b512, Digest};
+use k256::{
+    elliptic_curve::{
+        bigint::U512, group::GroupEncoding, ops::Reduce, rand_core::OsRng, Field, PrimeField as _,
+    },
+    ProjectivePoint, Scalar, WideBytes,
+};
+
+/// order of the secp256k1 group
+const ORDER: Uint<4> =
+    Uint::from_be_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141");
+
+impl AbelianMonoid for Scalar {
+    type Uint = Uint<4>;
+
+    fn one(&self) -> Self {
+        Scalar::ONE
+    }
+
+    fn pow(&self, exponent: &Self::Uint) -> Self {
+        self.pow_vartime(exponent.as_words())
+    }
+}
+
+impl Inv for Scalar {
+    fn inv(&self) -> Self {
+        self.invert().unwrap()
+    }
+}
+
+impl FiniteField for Scalar {
+    fn zero(&self) -> Self {
+        Scalar::ZERO
+    }
+
+    fn random(&self) -> Self {
+        <Scalar as Field>::random(&mut OsRng)
+    }
+
+    fn char(&self) -> Self::Uint {
+        ORDER
+    }
+
+    fn embed(&self, value: u64) -> Self {
+        Scalar::from(value)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.to_bytes().to_vec();
+        bytes.reverse();
+        bytes
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Option<Self> {
+        let mut bytes: [u8; 32] = bytes.try_into().ok()?;
+        bytes.reverse();
+        decode_scalar(&bytes)
+    }
+}
+
+/// hashes a sequence of byte strings to a scalar
+pub fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Blake2b512::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    let bytes: WideBytes = hasher.finalize();
+    <Scalar as Reduce<U512>>::reduce_bytes(&bytes)
+}
+
+/// hashes a byte string to a point of unknown discrete logarithm by try-and-increment
+pub fn hash_to_curve(msg: &[u8]) -> ProjectivePoint {
+    let mut hasher = Blake2b512::new();
+    hasher.update(msg);
+    (0u64..)
+        .find_map(|counter| {
+            let hash = hasher
+                .clone()
+                .chain_update(counter.to_le_bytes())
+                .finalize();
+            let mut bytes = [0u8; 33];
+            bytes[0] = 0x02 | (hash[32] & 1);
+            bytes[1..].copy_from_slice(&hash[..32]);
+            decode_point(&bytes)
+        })
+        .unwrap()
+}
+
+/// encodes a point in its 33-byte compressed form
+pub fn encode_point(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_bytes().to_vec()
+}
+
+/// decodes a point from its 33-byte compressed form
+pub fn decode_point(bytes: &[u8]) -> Option<ProjectivePoint> {
+    let bytes: [u8; 33] = bytes.try_into().ok()?;
+    ProjectivePoint::from_bytes(&bytes.into()).into()
+}
+
+/// decodes a scalar from its 32-byte big-endian form
+pub fn decode_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let bytes: [u8; 32] = bytes.try_into().ok()?;
+    Scalar::from_repr(bytes.into()).into()
+}
+
+/// encodes a sequence of points
+pub fn encode_points(points: &[ProjectivePoint]) -> Vec<u8> {
+    points.iter().flat_map(encode_point).collect()
+}
+
+/// decodes a sequence of points
+pub fn decode_points(bytes: &[u8]) -> Option<Vec<ProjectivePoint>> {
+    if !bytes.len().is_multiple_of(33) {
+        return None;
+    }
+    bytes.chunks(33).map(decode_point).collect()
+}
@@ -1,8 +1,13 @@
 //! implementations of MPC protocols
 
-// pub mod gentry;
+pub mod gentry;
+pub mod gmw;
+pub mod pir;
+pub mod psi;
 pub mod shamir;
-// pub mod yao;
+pub mod threshold;
+pub mod yao;
+pub mod zkp;
 
 /// defines security parameters in computational and statistical levels
 pub struct SecParams(u16, u16);
@@ -13,6 +18,10 @@ pub enum Adversary {
     Malicious,
 }
 
+pub mod circuit;
+pub mod dp;
+pub mod dpf;
 pub mod error;
+pub mod oprf;
 pub mod ot;
 pub mod party;
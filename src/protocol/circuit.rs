@@ -0,0 +1,558 @@
+//! boolean circuits shared by the protocols on bits
+//! Circuits are laid out as in Bristol Fashion, so that published circuits
+//! can be loaded by `bristol::parse` and evaluated by any protocol.
+
+/// gate of a boolean circuit, whose last operand is the output wire
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Gate {
+    /// `out = a ^ b`
+    Xor(usize, usize, usize),
+    /// `out = a & b`
+    And(usize, usize, usize),
+    /// `out = !a`
+    Inv(usize, usize),
+    /// `out = constant`
+    Eq(bool, usize),
+    /// `out = a`
+    Eqw(usize, usize),
+}
+
+/// boolean circuit laid out as in Bristol Fashion, i.e., input wires come
+/// first in order of the inputs, output wires come last, and every wire is
+/// written by gates before it is read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circuit {
+    /// number of wires
+    pub wires: usize,
+    /// number of bits of each input
+    pub inputs: Vec<usize>,
+    /// number of bits of each output
+    pub outputs: Vec<usize>,
+    /// gates in topological order
+    pub gates: Vec<Gate>,
+}
+
+impl Circuit {
+    /// number of AND gates, which dominate the cost of secure evaluation
+    pub fn and_count(&self) -> usize {
+        self.gates
+            .iter()
+            .filter(|gate| matches!(gate, Gate::And(..)))
+            .count()
+    }
+
+    /// indices of the input wires of input `i`
+    pub fn input_wires(&self, i: usize) -> std::ops::Range<usize> {
+        let start = self.inputs[..i].iter().sum();
+        start..start + self.inputs[i]
+    }
+
+    /// indices of the output wires
+    pub fn output_wires(&self) -> std::ops::Range<usize> {
+        self.wires - self.outputs.iter().sum::<usize>()..self.wires
+    }
+
+    /// evaluates the circuit in the clear on the concatenation of all inputs
+    pub fn eval(&self, inputs: &[bool]) -> Vec<bool> {
+        let mut values = vec![false; self.wires];
+        values[..inputs.len()].copy_from_slice(inputs);
+        for gate in &self.gates {
+            match *gate {
+                Gate::Xor(a, b, out) => values[out] = values[a] ^ values[b],
+                Gate::And(a, b, out) => values[out] = values[a] & values[b],
+                Gate::Inv(a, out) => values[out] = !values[a],
+                Gate::Eq(bit, out) => values[out] = bit,
+                Gate::Eqw(a, out) => values[out] = values[a],
+            }
+        }
+        values[self.output_wires()].to_vec()
+    }
+
+    /// splits the gates into layers by AND-depth, where a layer has the linear
+    /// gates of one depth followed by the AND gates taking inputs of that depth,
+    /// so that the AND gates of a layer can be evaluated at once
+    pub fn layers(&self) -> Vec<Vec<Gate>> {
+        let mut depths = vec![0; self.wires];
+        let mut layers: Vec<(Vec<Gate>, Vec<Gate>)> = vec![];
+        for gate in &self.gates {
+            let (depth, out) = match *gate {
+                Gate::Xor(a, b, out) | Gate::And(a, b, out) => (depths[a].max(depths[b]), out),
+                Gate::Inv(a, out) | Gate::Eqw(a, out) => (depths[a], out),
+                Gate::Eq(_, out) => (0, out),
+            };
+            if layers.len() <= depth {
+                layers.resize_with(depth + 1, Default::default);
+            }
+            if let Gate::And(..) = gate {
+                depths[out] = depth + 1;
+                layers[depth].1.push(*gate);
+            } else {
+                depths[out] = depth;
+                layers[depth].0.push(*gate);
+            }
+        }
+        layers
+            .into_iter()
+            .map(|(mut linear, ands)| {
+                linear.extend(ands);
+                linear
+            })
+            .collect()
+    }
+}
+
+/// encodes the lowest `width` bits of an integer, least significant first
+pub fn to_bits(value: u128, width: usize) -> Vec<bool> {
+    (0..width).map(|i| i < 128 && value >> i & 1 == 1).collect()
+}
+
+/// decodes an integer from bits, least significant first
+pub fn from_bits(bits: &[bool]) -> u128 {
+    bits.iter()
+        .rev()
+        .fold(0, |acc, &bit| acc << 1 | bit as u128)
+}
+
+/// builds a circuit gate by gate, where integers are given by their wires,
+/// least significant first
+/// # Example
+/// ```
+/// use dense::protocol::circuit::{to_bits, Builder};
+///
+/// let mut builder = Builder::new(&[8, 8]);
+/// let (a, b) = (builder.input(0), builder.input(1));
+/// let lt = builder.less_than(&a, &b);
+/// let max = builder.mux(lt, &a, &b);
+/// let circuit = builder.finish(&[max]);
+/// let inputs = [to_bits(3, 8), to_bits(5, 8)].concat();
+/// assert_eq!(to_bits(5, 8), circuit.eval(&inputs));
+/// ```
+pub struct Builder {
+    wires: usize,
+    inputs: Vec<usize>,
+    gates: Vec<Gate>,
+}
+
+impl Builder {
+    /// creates a builder of a circuit with inputs of given number of bits
+    pub fn new(inputs: &[usize]) -> Self {
+        Self {
+            wires: inputs.iter().sum(),
+            inputs: inputs.to_vec(),
+            gates: vec![],
+        }
+    }
+
+    /// wires of input `i`
+    pub fn input(&self, i: usize) -> Vec<usize> {
+        let start: usize = self.inputs[..i].iter().sum();
+        (start..start + self.inputs[i]).collect()
+    }
+
+    /// appends a gate writing a new wire
+    fn gate(&mut self, gate: impl FnOnce(usize) -> Gate) -> usize {
+        self.gates.push(gate(self.wires));
+        self.wires += 1;
+        self.wires - 1
+    }
+
+    pub fn xor(&mut self, a: usize, b: usize) -> usize {
+        self.gate(|out| Gate::Xor(a, b, out))
+    }
+
+    pub fn and(&mut self, a: usize, b: usize) -> usize {
+        self.gate(|out| Gate::And(a, b, out))
+    }
+
+    pub fn inv(&mut self, a: usize) -> usize {
+        self.gate(|out| Gate::Inv(a, out))
+    }
+
+    pub fn constant(&mut self, bit: bool) -> usize {
+        self.gate(|out| Gate::Eq(bit, out))
+    }
+
+    /// adds two integers of the same width, returning the sum with the carry as the last bit
+    pub fn add(&mut self, a: &[usize], b: &[usize]) -> Vec<usize> {
+        assert_eq!(a.len(), b.len());
+        let mut carry = self.constant(false);
+        let mut sum = Vec::with_capacity(a.len() + 1);
+        for (&a, &b) in a.iter().zip(b) {
+            // the carry is the majority of a, b and carry, with a single AND
+            let ab = self.xor(a, b);
+            sum.push(self.xor(ab, carry));
+            let ac = self.xor(a, carry);
+            let and = self.and(ab, ac);
+            carry = self.xor(and, a);
+        }
+        sum.push(carry);
+        sum
+    }
+
+    /// compares two unsigned integers of the same width, returning `a < b`
+    pub fn less_than(&mut self, a: &[usize], b: &[usize]) -> usize {
+        assert_eq!(a.len(), b.len());
+        // from the least significant bit, take the bit of b where the bits differ
+        let mut lt = self.constant(false);
+        for (&a, &b) in a.iter().zip(b) {
+            let ab = self.xor(a, b);
+            let bl = self.xor(b, lt);
+            let and = self.and(ab, bl);
+            lt = self.xor(lt, and);
+        }
+        lt
+    }
+
+    /// tests two integers of the same width for equality
+    pub fn equal(&mut self, a: &[usize], b: &[usize]) -> usize {
+        assert_eq!(a.len(), b.len());
+        let mut same: Vec<usize> = a
+            .iter()
+            .zip(b)
+            .map(|(&a, &b)| {
+                let ab = self.xor(a, b);
+                self.inv(ab)
+            })
+            .collect();
+        // multiply in a tree to keep the AND-depth logarithmic
+        while same.len() > 1 {
+            same = same
+                .chunks(2)
+                .map(|pair| match *pair {
+                    [a, b] => self.and(a, b),
+                    [a] => a,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        same.pop().unwrap_or_else(|| self.constant(true))
+    }
+
+    /// selects `b` if `select` is set or `a` otherwise
+    pub fn mux(&mut self, select: usize, a: &[usize], b: &[usize]) -> Vec<usize> {
+        assert_eq!(a.len(), b.len());
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| {
+                let ab = self.xor(a, b);
+                let and = self.and(select, ab);
+                self.xor(a, and)
+            })
+            .collect()
+    }
+
+    /// xors two integers of the same width bit by bit
+    fn xor_all(&mut self, a: &[usize], b: &[usize]) -> Vec<usize> {
+        a.iter().zip(b).map(|(&a, &b)| self.xor(a, b)).collect()
+    }
+
+    /// xors a byte with a constant, which inverts the wires of the set bits
+    fn xor_constant(&mut self, a: &[usize], constant: u8) -> Vec<usize> {
+        (0..8)
+            .map(|i| match constant >> i & 1 {
+                1 => self.inv(a[i]),
+                _ => a[i],
+            })
+            .collect()
+    }
+
+    /// multiplies a byte by `x` modulo the AES polynomial `x^8 + x^4 + x^3 + x + 1`
+    fn double(&mut self, a: &[usize]) -> Vec<usize> {
+        let mut doubled = vec![a[7]];
+        for i in 1..8 {
+            doubled.push(match i {
+                1 | 3 | 4 => self.xor(a[i - 1], a[7]),
+                _ => a[i - 1],
+            });
+        }
+        doubled
+    }
+
+    /// mixes a column of four bytes by AES MixColumns
+    fn mix_column(&mut self, column: &[Vec<usize>]) -> Vec<Vec<usize>> {
+        let all = column[1..]
+            .iter()
+            .fold(column[0].clone(), |acc, a| self.xor_all(&acc, a));
+        (0..4)
+            .map(|i| {
+                let next = self.xor_all(&column[i], &column[(i + 1) % 4]);
+                let doubled = self.double(&next);
+                let mixed = self.xor_all(&all, &doubled);
+                self.xor_all(&column[i], &mixed)
+            })
+            .collect()
+    }
+
+    /// substitutes a byte by the AES S-box with 34 AND gates, by the circuit of
+    /// Boyar and Peralta, A Depth-16 Circuit for the AES S-Box
+    fn sbox(&mut self, byte: &[usize]) -> Vec<usize> {
+        // the circuit numbers the bits from the most significant one
+        let u: Vec<usize> = byte.iter().rev().copied().collect();
+        let t1 = self.xor(u[0], u[3]);
+        let t2 = self.xor(u[0], u[5]);
+        let t3 = self.xor(u[0], u[6]);
+        let t4 = self.xor(u[3], u[5]);
+        let t5 = self.xor(u[4], u[6]);
+        let t6 = self.xor(t1, t5);
+        let t7 = self.xor(u[1], u[2]);
+        let t8 = self.xor(u[7], t6);
+        let t9 = self.xor(u[7], t7);
+        let t10 = self.xor(t6, t7);
+        let t11 = self.xor(u[1], u[5]);
+        let t12 = self.xor(u[2], u[5]);
+        let t13 = self.xor(t3, t4);
+        let t14 = self.xor(t6, t11);
+        let t15 = self.xor(t5, t11);
+        let t16 = self.xor(t5, t12);
+        let t17 = self.xor(t9, t16);
+        let t18 = self.xor(u[3], u[7]);
+        let t19 = self.xor(t7, t18);
+        let t20 = self.xor(t1, t19);
+        let t21 = self.xor(u[6], u[7]);
+        let t22 = self.xor(t7, t21);
+        let t23 = self.xor(t2, t22);
+        let t24 = self.xor(t2, t10);
+        let t25 = self.xor(t20, t17);
+        let t26 = self.xor(t3, t16);
+        let t27 = self.xor(t1, t12);
+        let m1 = self.and(t13, t6);
+        let m2 = self.and(t23, t8);
+        let m3 = self.xor(t14, m1);
+        let m4 = self.and(t19, u[7]);
+        let m5 = self.xor(m4, m1);
+        let m6 = self.and(t3, t16);
+        let m7 = self.and(t22, t9);
+        let m8 = self.xor(t26, m6);
+        let m9 = self.and(t20, t17);
+        let m10 = self.xor(m9, m6);
+        let m11 = self.and(t1, t15);
+        let m12 = self.and(t4, t27);
+        let m13 = self.xor(m12, m11);
+        let m14 = self.and(t2, t10);
+        let m15 = self.xor(m14, m11);
+        let m16 = self.xor(m3, m2);
+        let m17 = self.xor(m5, t24);
+        let m18 = self.xor(m8, m7);
+        let m19 = self.xor(m10, m15);
+        let m20 = self.xor(m16, m13);
+        let m21 = self.xor(m17, m15);
+        let m22 = self.xor(m18, m13);
+        let m23 = self.xor(m19, t25);
+        let m24 = self.xor(m22, m23);
+        let m25 = self.and(m22, m20);
+        let m26 = self.xor(m21, m25);
+        let m27 = self.xor(m20, m21);
+        let m28 = self.xor(m23, m25);
+        let m29 = self.and(m28, m27);
+        let m30 = self.and(m26, m24);
+        let m31 = self.and(m20, m23);
+        let m32 = self.and(m27, m31);
+        let m33 = self.xor(m27, m25);
+        let m34 = self.and(m21, m22);
+        let m35 = self.and(m24, m34);
+        let m36 = self.xor(m24, m25);
+        let m37 = self.xor(m21, m29);
+        let m38 = self.xor(m32, m33);
+        let m39 = self.xor(m23, m30);
+        let m40 = self.xor(m35, m36);
+        let m41 = self.xor(m38, m40);
+        let m42 = self.xor(m37, m39);
+        let m43 = self.xor(m37, m38);
+        let m44 = self.xor(m39, m40);
+        let m45 = self.xor(m42, m41);
+        let m46 = self.and(m44, t6);
+        let m47 = self.and(m40, t8);
+        let m48 = self.and(m39, u[7]);
+        let m49 = self.and(m43, t16);
+        let m50 = self.and(m38, t9);
+        let m51 = self.and(m37, t17);
+        let m52 = self.and(m42, t15);
+        let m53 = self.and(m45, t27);
+        let m54 = self.and(m41, t10);
+        let m55 = self.and(m44, t13);
+        let m56 = self.and(m40, t23);
+        let m57 = self.and(m39, t19);
+        let m58 = self.and(m43, t3);
+        let m59 = self.and(m38, t22);
+        let m60 = self.and(m37, t20);
+        let m61 = self.and(m42, t1);
+        let m62 = self.and(m45, t4);
+        let m63 = self.and(m41, t2);
+        let l0 = self.xor(m61, m62);
+        let l1 = self.xor(m50, m56);
+        let l2 = self.xor(m46, m48);
+        let l3 = self.xor(m47, m55);
+        let l4 = self.xor(m54, m58);
+        let l5 = self.xor(m49, m61);
+        let l6 = self.xor(m62, l5);
+        let l7 = self.xor(m46, l3);
+        let l8 = self.xor(m51, m59);
+        let l9 = self.xor(m52, m53);
+        let l10 = self.xor(m53, l4);
+        let l11 = self.xor(m60, l2);
+        let l12 = self.xor(m48, m51);
+        let l13 = self.xor(m50, l0);
+        let l14 = self.xor(m52, m61);
+        let l15 = self.xor(m55, l1);
+        let l16 = self.xor(m56, l0);
+        let l17 = self.xor(m57, l1);
+        let l18 = self.xor(m58, l8);
+        let l19 = self.xor(m63, l4);
+        let l20 = self.xor(l0, l1);
+        let l21 = self.xor(l1, l7);
+        let l22 = self.xor(l3, l12);
+        let l23 = self.xor(l18, l2);
+        let l24 = self.xor(l15, l9);
+        let l25 = self.xor(l6, l10);
+        let l26 = self.xor(l7, l9);
+        let l27 = self.xor(l8, l10);
+        let l28 = self.xor(l11, l14);
+        let l29 = self.xor(l11, l17);
+        let s0 = self.xor(l6, l24);
+        let s1 = self.xor(l16, l26);
+        let s1 = self.inv(s1);
+        let s2 = self.xor(l19, l28);
+        let s2 = self.inv(s2);
+        let s3 = self.xor(l6, l21);
+        let s4 = self.xor(l20, l22);
+        let s5 = self.xor(l25, l29);
+        let s6 = self.xor(l13, l27);
+        let s6 = self.inv(s6);
+        let s7 = self.xor(l6, l23);
+        let s7 = self.inv(s7);
+        vec![s7, s6, s5, s4, s3, s2, s1, s0]
+    }
+
+    /// finishes the circuit by copying every output to the last wires
+    pub fn finish(mut self, outputs: &[Vec<usize>]) -> Circuit {
+        for &wire in outputs.iter().flatten() {
+            self.gate(|out| Gate::Eqw(wire, out));
+        }
+        Circuit {
+            wires: self.wires,
+            inputs: self.inputs,
+            outputs: outputs.iter().map(Vec::len).collect(),
+            gates: self.gates,
+        }
+    }
+}
+
+impl Circuit {
+    /// adds two unsigned integers of `width` bits into `width + 1` bits
+    pub fn adder(width: usize) -> Self {
+        let mut builder = Builder::new(&[width, width]);
+        let (a, b) = (builder.input(0), builder.input(1));
+        let sum = builder.add(&a, &b);
+        builder.finish(&[sum])
+    }
+
+    /// compares two unsigned integers of `width` bits into `a < b`
+    pub fn comparator(width: usize) -> Self {
+        let mut builder = Builder::new(&[width, width]);
+        let (a, b) = (builder.input(0), builder.input(1));
+        let lt = builder.less_than(&a, &b);
+        builder.finish(&[vec![lt]])
+    }
+
+    /// tests two integers of `width` bits into `a == b`
+    pub fn equality(width: usize) -> Self {
+        let mut builder = Builder::new(&[width, width]);
+        let (a, b) = (builder.input(0), builder.input(1));
+        let eq = builder.equal(&a, &b);
+        builder.finish(&[vec![eq]])
+    }
+
+    /// encrypts a block by AES-128, where the inputs are the key and the
+    /// plaintext and the output is the ciphertext, each of 16 bytes in order
+    pub fn aes128() -> Self {
+        let mut builder = Builder::new(&[128, 128]);
+        let bytes = |wires: Vec<usize>| -> Vec<Vec<usize>> {
+            wires.chunks(8).map(<[usize]>::to_vec).collect()
+        };
+        let mut round_key = bytes(builder.input(0));
+        let mut state: Vec<Vec<usize>> = bytes(builder.input(1))
+            .iter()
+            .zip(&round_key)
+            .map(|(s, k)| builder.xor_all(s, k))
+            .collect();
+        let mut rcon = 1u8;
+        for round in 1..=10 {
+            // the next round key starts from the rotated and substituted last word
+            let mut word: Vec<Vec<usize>> = [13, 14, 15, 12]
+                .iter()
+                .map(|&i| builder.sbox(&round_key[i]))
+                .collect();
+            word[0] = builder.xor_constant(&word[0], rcon);
+            rcon = rcon << 1 ^ if rcon & 0x80 == 0 { 0 } else { 0x1b };
+            for i in 0..16 {
+                round_key[i] = builder.xor_all(&round_key[i], &word[i % 4]);
+                word[i % 4] = round_key[i].clone();
+            }
+            state = state.iter().map(|byte| builder.sbox(byte)).collect();
+            // row r of the column-major state shifts left by r
+            state = (0..16)
+                .map(|i| state[i % 4 + 4 * ((i / 4 + i % 4) % 4)].clone())
+                .collect();
+            if round < 10 {
+                state = state
+                    .chunks(4)
+                    .flat_map(|column| builder.mix_column(column))
+                    .collect();
+            }
+            state = state
+                .iter()
+                .zip(&round_key)
+                .map(|(s, k)| builder.xor_all(s, k))
+                .collect();
+        }
+        builder.finish(&[state.concat()])
+    }
+}
+
+pub mod bristol;
+
+#[test]
+fn test_correctness() {
+    let cases = [(0, 0), (1, 2), (200, 100), (255, 255), (128, 127)];
+    for (a, b) in cases {
+        let inputs = [to_bits(a, 8), to_bits(b, 8)].concat();
+        assert_eq!(a + b, from_bits(&Circuit::adder(8).eval(&inputs)));
+        assert_eq!(vec![a < b], Circuit::comparator(8).eval(&inputs));
+        assert_eq!(vec![a == b], Circuit::equality(8).eval(&inputs));
+    }
+    let mut builder = Builder::new(&[1, 4, 4]);
+    let (select, a, b) = (builder.input(0)[0], builder.input(1), builder.input(2));
+    let mux = builder.mux(select, &a, &b);
+    let mux = builder.finish(&[mux]);
+    for select in [false, true] {
+        let inputs = [vec![select], to_bits(5, 4), to_bits(9, 4)].concat();
+        assert_eq!(if select { 9 } else { 5 }, from_bits(&mux.eval(&inputs)));
+    }
+    // the comparator chains one AND gate per bit, while equality multiplies in a tree
+    let ands = |layer: &Vec<Gate>| layer.iter().filter(|g| matches!(g, Gate::And(..))).count();
+    let layers = Circuit::comparator(8).layers();
+    assert_eq!(vec![1; 8], layers[..8].iter().map(ands).collect::<Vec<_>>());
+    assert_eq!(0, ands(&layers[8]));
+    let layers = Circuit::equality(8).layers();
+    assert_eq!(
+        vec![4, 2, 1, 0],
+        layers.iter().map(ands).collect::<Vec<_>>()
+    );
+    // the worked example of FIPS-197 Appendix B
+    let bits = |hex: &str| -> Vec<bool> {
+        (0..hex.len())
+            .step_by(2)
+            .flat_map(|i| to_bits(u128::from_str_radix(&hex[i..i + 2], 16).unwrap(), 8))
+            .collect()
+    };
+    let aes = Circuit::aes128();
+    assert_eq!(6800, aes.and_count());
+    let inputs = [
+        bits("2b7e151628aed2a6abf7158809cf4f3c"),
+        bits("3243f6a8885a308d313198a2e0370734"),
+    ];
+    assert_eq!(
+        bits("3925841d02dc09fbdc118597196a0b32"),
+        aes.eval(&inputs.concat())
+    );
+}
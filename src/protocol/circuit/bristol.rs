@@ -0,0 +1,173 @@
+//! parses and writes circuits in Bristol Fashion
+//! https://nigelsmart.github.io/MPC-Circuits/
+//! A file starts with the number of gates and wires, then the number and
+//! widths of the inputs and outputs, followed by one gate per line, e.g.,
+//! `2 1 a b out XOR` or `1 1 a out INV`.
+
+use super::{Circuit, Gate};
+use crate::store::{ParseError, StoreValue};
+use std::collections::HashSet;
+
+/// parses a circuit, where MAND gates are split into AND gates, and fails
+/// unless every gate reads input wires or wires written by earlier gates and
+/// every output wire is written
+pub fn parse(text: &str) -> Result<Circuit, ParseError> {
+    let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+    let mut numbers = || -> Result<Vec<usize>, ParseError> {
+        lines
+            .next()
+            .ok_or(ParseError)?
+            .split_whitespace()
+            .map(|token| token.parse().map_err(|_| ParseError))
+            .collect()
+    };
+    let header = numbers()?;
+    let inputs = numbers()?;
+    let outputs = numbers()?;
+    let (&[count, wires], Some((&ni, inputs)), Some((&no, outputs))) = (
+        header.as_slice(),
+        inputs.split_first(),
+        outputs.split_first(),
+    ) else {
+        return Err(ParseError);
+    };
+    if ni != inputs.len() || no != outputs.len() {
+        return Err(ParseError);
+    }
+    let mut gates = Vec::with_capacity(count);
+    let mut lines_read = 0;
+    for line in lines {
+        lines_read += 1;
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let (&op, tokens) = tokens.split_last().ok_or(ParseError)?;
+        let tokens: Vec<usize> = tokens
+            .iter()
+            .map(|token| token.parse().map_err(|_| ParseError))
+            .collect::<Result<_, _>>()?;
+        let wire = |w: usize| if w < wires { Ok(w) } else { Err(ParseError) };
+        match (op, tokens.as_slice()) {
+            ("XOR", &[2, 1, a, b, out]) => gates.push(Gate::Xor(wire(a)?, wire(b)?, wire(out)?)),
+            ("AND", &[2, 1, a, b, out]) => gates.push(Gate::And(wire(a)?, wire(b)?, wire(out)?)),
+            ("INV", &[1, 1, a, out]) => gates.push(Gate::Inv(wire(a)?, wire(out)?)),
+            ("EQ", &[1, 1, bit @ (0 | 1), out]) => gates.push(Gate::Eq(bit == 1, wire(out)?)),
+            ("EQW", &[1, 1, a, out]) => gates.push(Gate::Eqw(wire(a)?, wire(out)?)),
+            ("MAND", &[n, m, ref wires @ ..]) if n == 2 * m && wires.len() == 3 * m => {
+                for i in 0..m {
+                    let (a, b, out) = (wires[i], wires[m + i], wires[2 * m + i]);
+                    gates.push(Gate::And(wire(a)?, wire(b)?, wire(out)?));
+                }
+            }
+            _ => return Err(ParseError),
+        }
+    }
+    let circuit = Circuit {
+        wires,
+        inputs: inputs.to_vec(),
+        outputs: outputs.to_vec(),
+        gates,
+    };
+    if lines_read != count || circuit.inputs.iter().chain(&circuit.outputs).sum::<usize>() > wires {
+        return Err(ParseError);
+    }
+    // the gates are in topological order
+    let inputs: usize = circuit.inputs.iter().sum();
+    let mut written = HashSet::new();
+    for gate in &circuit.gates {
+        let (reads, out) = match *gate {
+            Gate::Xor(a, b, out) | Gate::And(a, b, out) => (vec![a, b], out),
+            Gate::Inv(a, out) | Gate::Eqw(a, out) => (vec![a], out),
+            Gate::Eq(_, out) => (vec![], out),
+        };
+        if reads.iter().any(|w| *w >= inputs && !written.contains(w)) {
+            return Err(ParseError);
+        }
+        written.insert(out);
+    }
+    let outputs = circuit.output_wires();
+    if outputs.len() > written.len() || !outputs.into_iter().all(|w| written.contains(&w)) {
+        return Err(ParseError);
+    }
+    Ok(circuit)
+}
+
+/// writes a circuit, which `parse` reads back
+pub fn write(circuit: &Circuit) -> String {
+    let widths = |widths: &[usize]| {
+        std::iter::once(widths.len())
+            .chain(widths.iter().copied())
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut text = format!(
+        "{} {}\n{}\n{}\n\n",
+        circuit.gates.len(),
+        circuit.wires,
+        widths(&circuit.inputs),
+        widths(&circuit.outputs)
+    );
+    for gate in &circuit.gates {
+        let line = match *gate {
+            Gate::Xor(a, b, out) => format!("2 1 {a} {b} {out} XOR"),
+            Gate::And(a, b, out) => format!("2 1 {a} {b} {out} AND"),
+            Gate::Inv(a, out) => format!("1 1 {a} {out} INV"),
+            Gate::Eq(bit, out) => format!("1 1 {} {out} EQ", bit as u8),
+            Gate::Eqw(a, out) => format!("1 1 {a} {out} EQW"),
+        };
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+/// stores a circuit as its Bristol Fashion text
+impl StoreValue for Circuit {
+    fn to_bytes(self) -> Vec<u8> {
+        write(&self).into_bytes()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        parse(std::str::from_utf8(bytes).map_err(|_| ParseError)?)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::{from_bits, to_bits};
+
+    // 2-bit adder modulo 4
+    let text = "
+        5 9
+        2 2 2
+        1 2
+
+        2 1 0 2 4 XOR
+        2 1 1 3 5 XOR
+        2 1 0 2 6 AND
+        2 1 6 5 8 XOR
+        1 1 4 7 EQW
+        ";
+    let circuit = parse(text).unwrap();
+    assert_eq!(vec![2, 2], circuit.inputs);
+    for (a, b) in [(0, 0), (1, 2), (3, 1), (2, 3)] {
+        let inputs = [to_bits(a, 2), to_bits(b, 2)].concat();
+        assert_eq!((a + b) % 4, from_bits(&circuit.eval(&inputs)));
+    }
+    let mand = "1 5\n1 2\n1 2\n2 2 0 1 2 3 4 4 MAND\n";
+    assert!(parse(mand).is_err());
+    let mand = "1 6\n1 2\n1 2\n4 2 0 0 1 1 4 5 MAND\n";
+    assert_eq!(2, parse(mand).unwrap().and_count());
+    assert!(parse("1 3\n1 2\n1 1\n2 1 0 1 3 XOR\n").is_err());
+    assert!(parse("1 3\n1 2\n1 1\n2 1 0 1 2 NAND\n").is_err());
+    // a gate reads a wire before it is written, or an output is never written
+    assert!(parse("2 4\n2 1 1\n1 1\n2 1 0 3 2 XOR\n2 1 0 1 3 AND\n").is_err());
+    assert!(parse("2 4\n2 1 1\n1 1\n2 1 0 1 2 AND\n2 1 0 2 3 XOR\n").is_ok());
+    assert!(parse("1 4\n2 1 1\n1 1\n2 1 0 1 2 XOR\n").is_err());
+
+    let adder = Circuit::adder(8);
+    assert_eq!(adder, parse(&write(&adder)).unwrap());
+    assert_eq!(
+        adder,
+        Circuit::from_bytes(&adder.clone().to_bytes()).unwrap()
+    );
+}
@@ -0,0 +1,464 @@
+//! implements differentially private release of aggregates
+//! The Discrete Gaussian for Differential Privacy
+//! https://arxiv.org/abs/2004.00010
+//! Noise is sampled from the discrete Laplace or the discrete Gaussian
+//! distribution, so that it can be added to integers in a field. In a
+//! distributed release, every party shares its own noise and the sum is added
+//! before opening, so that no party knows the noise. Laplace noise is sampled
+//! at full scale by each party, and Gaussian noise splits its variance among
+//! the parties that are honest when `T` shares recover a secret. The budget
+//! spent on each dataset is kept in a `Store` and checked against its limit.
+//! Every dataset is either put under a limit or exempted explicitly, so that a
+//! policy that cannot be read fails the release instead of lifting the limit.
+
+use super::error::{Error, MPCErrorKind, Result};
+use super::shamir::aggregate::{decode, embed, Column, Numeric, Selection};
+use super::shamir::{bgw88::Evaluator, SSServer};
+use crate::math::galois::FiniteField;
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::store::{ParseError, Store, StoreValue};
+use std::io::ErrorKind;
+
+/// samples uniformly from `[0, 1)`
+fn uniform() -> f64 {
+    (OsRng.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// samples a bit that is one with probability `exp(-x)`
+fn bernoulli_exp(x: f64) -> bool {
+    uniform() < (-x).exp()
+}
+
+/// samples the number of failures before a success, which occurs with probability `1 - exp(-1 / t)`
+fn geometric(t: f64) -> i128 {
+    (-(1.0 - uniform()).ln() * t).floor() as i128
+}
+
+/// samples an integer with probability proportional to `exp(-|y| / t)`
+fn discrete_laplace(t: f64) -> i128 {
+    geometric(t) - geometric(t)
+}
+
+/// samples an integer with probability proportional to `exp(-y^2 / (2 sigma^2))`,
+/// by rejection from the discrete Laplace distribution
+fn discrete_gaussian(sigma: f64) -> i128 {
+    if sigma == 0.0 {
+        return 0;
+    }
+    let t = sigma.floor() + 1.0;
+    loop {
+        let u = (uniform() * t).floor();
+        if !bernoulli_exp(u / t) {
+            continue;
+        }
+        let mut v = 0.0;
+        while bernoulli_exp(1.0) {
+            v += 1.0;
+        }
+        let y = u + t * v;
+        let negative = OsRng.next_u32() & 1 == 1;
+        if negative && y == 0.0 {
+            continue;
+        }
+        if bernoulli_exp((y - sigma * sigma / t).powi(2) / (2.0 * sigma * sigma)) {
+            return if negative { -(y as i128) } else { y as i128 };
+        }
+    }
+}
+
+/// privacy loss of `(epsilon, delta)`-differential privacy, which adds up under composition
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    pub epsilon: f64,
+    pub delta: f64,
+}
+
+impl Budget {
+    pub fn new(epsilon: f64, delta: f64) -> Self {
+        Self { epsilon, delta }
+    }
+
+    /// tests whether this budget fits within `limit`, with a tolerance of rounding
+    pub fn within(&self, limit: &Budget) -> bool {
+        const TOLERANCE: f64 = 1e-9;
+        self.epsilon <= limit.epsilon + TOLERANCE && self.delta <= limit.delta + TOLERANCE
+    }
+}
+
+impl std::ops::Add for Budget {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.epsilon + other.epsilon, self.delta + other.delta)
+    }
+}
+
+/// stores epsilon and delta as little-endian f64
+impl StoreValue for Budget {
+    fn to_bytes(self) -> Vec<u8> {
+        [self.epsilon.to_le_bytes(), self.delta.to_le_bytes()].concat()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, ParseError> {
+        let bytes: [u8; 16] = bytes.try_into().map_err(|_| ParseError)?;
+        let (epsilon, delta) = bytes.split_at(8);
+        let budget = Self::new(
+            f64::from_le_bytes(epsilon.try_into().unwrap()),
+            f64::from_le_bytes(delta.try_into().unwrap()),
+        );
+        match budget.epsilon >= 0.0 && budget.delta >= 0.0 {
+            true => Ok(budget),
+            false => Err(ParseError),
+        }
+    }
+}
+
+/// noise added to a query whose result changes by at most a sensitivity,
+/// in the L1 norm for Laplace and in the L2 norm for Gaussian noise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mechanism {
+    /// discrete Laplace noise of scale `sensitivity / epsilon`, which is `(epsilon, 0)`-DP
+    Laplace { epsilon: f64 },
+    /// discrete Gaussian noise of deviation `sensitivity * sqrt(2 ln(1.25 / delta)) / epsilon`,
+    /// which is `(epsilon, delta)`-DP for epsilon at most one
+    Gaussian { epsilon: f64, delta: f64 },
+}
+
+impl Mechanism {
+    /// budget spent by one release
+    pub fn cost(&self) -> Budget {
+        match *self {
+            Mechanism::Laplace { epsilon } => Budget::new(epsilon, 0.0),
+            Mechanism::Gaussian { epsilon, delta } => Budget::new(epsilon, delta),
+        }
+    }
+
+    /// tests whether the parameters lie where the guarantee holds
+    fn valid(&self) -> bool {
+        match *self {
+            Mechanism::Laplace { epsilon } => epsilon > 0.0,
+            Mechanism::Gaussian { epsilon, delta } => {
+                0.0 < epsilon && epsilon <= 1.0 && 0.0 < delta && delta < 1.0
+            }
+        }
+    }
+
+    /// samples the noise of one party out of `honest` parties, whose noise sums up
+    /// to at least the noise of the mechanism
+    fn noise(&self, sensitivity: u64, honest: usize) -> i128 {
+        assert!(
+            self.valid(),
+            "the parameters are out of the range of the mechanism"
+        );
+        match *self {
+            Mechanism::Laplace { epsilon } => discrete_laplace(sensitivity as f64 / epsilon),
+            Mechanism::Gaussian { epsilon, delta } => {
+                let sigma = sensitivity as f64 * (2.0 * (1.25 / delta).ln()).sqrt() / epsilon;
+                discrete_gaussian(sigma / (honest as f64).sqrt())
+            }
+        }
+    }
+
+    /// samples the noise of the mechanism, as a trusted curator does
+    pub fn sample(&self, sensitivity: u64) -> i128 {
+        self.noise(sensitivity, 1)
+    }
+}
+
+/// keeps the limit and the spent budget of each dataset in a store
+pub struct Accountant<S: Store> {
+    store: S,
+}
+
+impl<S: Store> Accountant<S> {
+    pub fn new(store: S) -> Self {
+        Self { store }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    /// records the policy of the dataset as the bytes of its limit, which are
+    /// empty if it is exempt, and starts its spent budget at zero unless it is kept
+    fn register(&mut self, dataset: &str, limit: Vec<u8>) -> Result<()> {
+        if self.spent(dataset).is_err() {
+            self.store
+                .set(&format!("{dataset}:spent"), &Budget::default())
+                .map_err(|_| Error::IOError(ErrorKind::Other))?;
+        }
+        self.store
+            .set(&format!("{dataset}:limit"), &limit)
+            .map_err(|_| Error::IOError(ErrorKind::Other))
+    }
+
+    /// puts the dataset under a policy, which requires noise on every release
+    pub fn set_limit(&mut self, dataset: &str, limit: Budget) -> Result<()> {
+        self.register(dataset, limit.to_bytes())
+    }
+
+    /// exempts the dataset from any policy, which releases its exact aggregates
+    pub fn exempt(&mut self, dataset: &str) -> Result<()> {
+        self.register(dataset, vec![])
+    }
+
+    /// reads the raw bytes of a key, which fails on any error of the store
+    fn read(&self, key: &str) -> Result<Vec<u8>> {
+        self.store
+            .get::<Vec<u8>>(key)
+            .map_err(|_| Error::IOError(ErrorKind::NotFound))
+    }
+
+    /// gets the limit of the dataset, or None if it is exempt. It fails if
+    /// neither is recorded, or if the limit is corrupt.
+    pub fn limit(&self, dataset: &str) -> Result<Option<Budget>> {
+        let bytes = self.read(&format!("{dataset}:limit"))?;
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        Budget::from_bytes(&bytes)
+            .map(Some)
+            .map_err(|_| Error::IOError(ErrorKind::InvalidData))
+    }
+
+    /// gets the budget spent on the dataset, which is zero when it is registered
+    pub fn spent(&self, dataset: &str) -> Result<Budget> {
+        Budget::from_bytes(&self.read(&format!("{dataset}:spent"))?)
+            .map_err(|_| Error::IOError(ErrorKind::InvalidData))
+    }
+
+    /// records the cost of a release, and fails without recording it if the
+    /// limit would be exceeded or the policy cannot be read
+    pub fn charge(&mut self, dataset: &str, cost: Budget) -> Result<()> {
+        let limit = self.limit(dataset)?;
+        let spent = self.spent(dataset)? + cost;
+        if limit.is_some_and(|limit| !spent.within(&limit)) {
+            return Err(Error::MPCError(MPCErrorKind::BudgetExceeded));
+        }
+        self.store
+            .set(&format!("{dataset}:spent"), &spent)
+            .map_err(|_| Error::IOError(ErrorKind::Other))
+    }
+
+    /// protects the column if the dataset is under a policy, and fails if
+    /// the policy cannot be read
+    pub fn protect<F>(&self, dataset: &str, column: &mut Column<F>) -> Result<()> {
+        if self.limit(dataset)?.is_some() {
+            column.protect();
+        }
+        Ok(())
+    }
+}
+
+impl<const T: usize, const N: usize, F, S> Evaluator<T, N, F, S>
+where
+    F: FiniteField,
+    S: SSServer<T, N, F>,
+{
+    /// shares noise of the mechanism that no party knows, as the sum of the noise of every party
+    pub fn noise(&self, mechanism: &Mechanism, sensitivity: u64, count: usize) -> Result<Vec<F>> {
+        let field = *self.field();
+        let mut noise = vec![field.zero(); count];
+        for owner in 0..N {
+            let values: Vec<i128> = match owner == self.id() {
+                true => (0..count)
+                    .map(|_| mechanism.noise(sensitivity, N - T + 1))
+                    .collect(),
+                false => vec![],
+            };
+            let values: Vec<F> = values.into_iter().map(|v| embed(&field, v)).collect();
+            let shares = self.input(owner, &values)?;
+            if shares.len() != count {
+                return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+            }
+            noise.iter_mut().zip(shares).for_each(|(n, s)| *n += s);
+        }
+        Ok(noise)
+    }
+
+    /// charges the dataset, and opens the shared values with noise. It fails
+    /// without charging if the parameters of the mechanism are out of range.
+    fn release<A: Store>(
+        &self,
+        accountant: &mut Accountant<A>,
+        dataset: &str,
+        values: &[F],
+        mechanism: &Mechanism,
+        sensitivity: u64,
+    ) -> Result<Vec<i128>> {
+        if !mechanism.valid() {
+            return Err(Error::IOError(ErrorKind::InvalidInput));
+        }
+        accountant.charge(dataset, mechanism.cost())?;
+        let noise = self.noise(mechanism, sensitivity, values.len())?;
+        let noisy: Vec<F> = values.iter().zip(noise).map(|(v, n)| *v + n).collect();
+        self.open(&noisy)?.iter().map(decode).collect()
+    }
+
+    /// releases the sum of the column, or of the selected rows, where
+    /// `sensitivity` bounds the magnitude of each value
+    pub fn release_sum<A: Store>(
+        &self,
+        accountant: &mut Accountant<A>,
+        dataset: &str,
+        column: &Column<F>,
+        selection: Option<&Selection<F>>,
+        mechanism: &Mechanism,
+        sensitivity: u64,
+    ) -> Result<i128> {
+        let sum = self.total(column, selection)?;
+        Ok(self.release(accountant, dataset, &[sum], mechanism, sensitivity)?[0])
+    }
+
+    /// releases the number of rows of the column, or of the selected rows
+    pub fn release_count<A: Store>(
+        &self,
+        accountant: &mut Accountant<A>,
+        dataset: &str,
+        column: &Column<F>,
+        selection: Option<&Selection<F>>,
+        mechanism: &Mechanism,
+    ) -> Result<i128> {
+        let count = self.tally(column, selection);
+        Ok(self.release(accountant, dataset, &[count], mechanism, 1)?[0])
+    }
+
+    /// releases the histogram of the column, where a row changes a single bucket by one
+    pub fn release_histogram<A: Store, V: Numeric>(
+        &self,
+        accountant: &mut Accountant<A>,
+        dataset: &str,
+        column: &Column<F>,
+        boundaries: &[V],
+        mechanism: &Mechanism,
+    ) -> Result<Vec<i128>> {
+        let counts = self.buckets(column, boundaries)?;
+        self.release(accountant, dataset, &counts, mechanism, 1)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::party::MultiParty;
+    use super::shamir::s79::Server;
+    use crate::math::PrimeModulus;
+    use crate::store::disk::DiskStore;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    // the samplers have the variance of their distributions
+    let variance = |samples: &[i128]| -> f64 {
+        let mean = samples.iter().sum::<i128>() as f64 / samples.len() as f64;
+        samples
+            .iter()
+            .map(|&x| (x as f64 - mean).powi(2))
+            .sum::<f64>()
+            / samples.len() as f64
+    };
+    let laplace = Mechanism::Laplace { epsilon: 0.5 };
+    let samples: Vec<i128> = (0..4000).map(|_| laplace.sample(5)).collect();
+    let q = (-0.1f64).exp();
+    let expected = 2.0 * q / (1.0 - q).powi(2);
+    assert!((variance(&samples) / expected - 1.0).abs() < 0.2);
+    let samples: Vec<i128> = (0..4000).map(|_| discrete_gaussian(7.5)).collect();
+    assert!((variance(&samples) / 56.25 - 1.0).abs() < 0.2);
+
+    let temperatures: Vec<i8> = vec![12, -5, 23, 0, 17, 31, 8, 19, -12, 27];
+    let gaussian = Mechanism::Gaussian {
+        epsilon: 0.5,
+        delta: 1e-6,
+    };
+    let field = PrimeModulus::<1>::from_random(62).random_make();
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9210 + i as u16)));
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                let temperatures = &temperatures;
+                scope.spawn(move || {
+                    let docname = format!("budgets-{id}");
+                    let _ = std::fs::remove_file(format!("data/Alice/{docname}"));
+                    let mut store = DiskStore::new("data/Alice", &docname).unwrap();
+                    store.set("corrupt:limit", &7u8).unwrap();
+                    let mut accountant = Accountant::new(store);
+                    // a corrupt or missing policy fails rather than lifting the limit
+                    let invalid = Error::IOError(ErrorKind::InvalidData);
+                    assert_eq!(Err(invalid), accountant.limit("corrupt"));
+                    assert_eq!(Err(invalid), accountant.charge("corrupt", laplace.cost()));
+                    let missing = Error::IOError(ErrorKind::NotFound);
+                    assert_eq!(Err(missing), accountant.limit("weather"));
+                    assert_eq!(Err(missing), accountant.charge("weather", laplace.cost()));
+                    accountant.exempt("public").unwrap();
+                    assert_eq!(Ok(None), accountant.limit("public"));
+                    accountant.charge("public", laplace.cost()).unwrap();
+                    assert_eq!(Ok(laplace.cost()), accountant.spent("public"));
+                    accountant
+                        .set_limit("weather", Budget::new(1.2, 1e-5))
+                        .unwrap();
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    let evaluator = Evaluator::new(party, Server::<2, 3>, field);
+                    let values = if id == 0 {
+                        temperatures.clone()
+                    } else {
+                        vec![]
+                    };
+                    let mut column = evaluator.input_column(0, &values).unwrap();
+                    accountant.protect("weather", &mut column).unwrap();
+                    let exact = evaluator.sum(&column, None);
+                    // the Gaussian guarantee needs epsilon at most one, and nothing is charged
+                    let loose = Mechanism::Gaussian {
+                        epsilon: 2.0,
+                        delta: 1e-6,
+                    };
+                    assert_eq!(
+                        Err(Error::IOError(ErrorKind::InvalidInput)),
+                        evaluator.release_sum(&mut accountant, "weather", &column, None, &loose, 1)
+                    );
+                    let sum = evaluator
+                        .release_sum(&mut accountant, "weather", &column, None, &gaussian, 128)
+                        .unwrap();
+                    let histogram = evaluator
+                        .release_histogram(
+                            &mut accountant,
+                            "weather",
+                            &column,
+                            &[-128i8, 0, 20, 127],
+                            &laplace,
+                        )
+                        .unwrap();
+                    // a third release exceeds epsilon, and is not charged
+                    let exceeded = evaluator.release_count(
+                        &mut accountant,
+                        "weather",
+                        &column,
+                        None,
+                        &laplace,
+                    );
+                    (
+                        exact,
+                        sum,
+                        histogram,
+                        exceeded,
+                        accountant.spent("weather").unwrap(),
+                    )
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let sum: i128 = temperatures.iter().map(|&t| t as i128).sum();
+    let sigma = 128.0 * (2.0 * (1.25e6f64).ln()).sqrt() / 0.5;
+    for result in &results {
+        assert_eq!(
+            Err(Error::MPCError(MPCErrorKind::PolicyViolation)),
+            result.0
+        );
+        assert_eq!((results[0].1, &results[0].2), (result.1, &result.2));
+        assert!(((result.1 - sum) as f64).abs() < 8.0 * sigma);
+        for (noisy, exact) in result.2.iter().zip([2, 5, 3]) {
+            assert!((noisy - exact).abs() < 200);
+        }
+        assert_eq!(Err(Error::MPCError(MPCErrorKind::BudgetExceeded)), result.3);
+        assert_eq!(Budget::new(1.0, 1e-6), result.4);
+    }
+}
@@ -0,0 +1,241 @@
+//! implements distributed point functions
+//! Function Secret Sharing: Improvements and Extensions
+//! https://eprint.iacr.org/2018/707.pdf
+//! A point function is `beta` at `alpha` and zero elsewhere on a domain of
+//! `2^bits` points. It is shared into two keys, each a seed and a correction
+//! word per level of a binary tree, so that the XOR of both evaluations is the
+//! function while a single key hides `alpha` and `beta`.
+
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::store::{ParseError, StoreValue};
+use blake2::{Blake2b512, Digest};
+
+/// seed of the tree nodes
+type Seed = u128;
+
+/// expands a seed into the seeds and control bits of both children
+fn prg(seed: Seed) -> [(Seed, bool); 2] {
+    let hash = Blake2b512::digest(seed.to_le_bytes());
+    let child = |i: usize| {
+        let bytes = hash[16 * i..16 * (i + 1)].try_into().unwrap();
+        (Seed::from_le_bytes(bytes), hash[32 + i] & 1 == 1)
+    };
+    [child(0), child(1)]
+}
+
+fn random_seed() -> Seed {
+    OsRng.next_u64() as Seed | (OsRng.next_u64() as Seed) << 64
+}
+
+/// expands a leaf seed into `len` output bytes
+fn convert(seed: Seed, len: usize) -> Vec<u8> {
+    let mut output = Vec::with_capacity(len);
+    for counter in 0..len.div_ceil(64) as u64 {
+        let mut hasher = Blake2b512::new();
+        hasher.update(b"convert");
+        hasher.update(seed.to_le_bytes());
+        hasher.update(counter.to_le_bytes());
+        output.extend(hasher.finalize());
+    }
+    output.truncate(len);
+    output
+}
+
+fn xor(a: &mut [u8], b: &[u8]) {
+    a.iter_mut().zip(b).for_each(|(a, b)| *a ^= b);
+}
+
+/// correction of a level, applied to both children of nodes whose control bit is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CorrectionWord {
+    seed: Seed,
+    bits: [bool; 2],
+}
+
+/// key of one of the two parties
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    seed: Seed,
+    party: bool,
+    words: Vec<CorrectionWord>,
+    /// correction of the outputs
+    output: Vec<u8>,
+}
+
+/// shares the point function of `beta` at `alpha` on `2^bits` points
+pub fn generate(alpha: usize, bits: u32, beta: &[u8]) -> [Key; 2] {
+    assert!(
+        bits <= 32 && alpha >> bits == 0,
+        "alpha must lie in the domain"
+    );
+    let roots = [random_seed(), random_seed()];
+    let (mut seeds, mut controls) = (roots, [false, true]);
+    let mut words = Vec::with_capacity(bits as usize);
+    for level in (0..bits).rev() {
+        let keep = alpha >> level & 1;
+        let lose = 1 - keep;
+        let children = seeds.map(prg);
+        let word = CorrectionWord {
+            seed: children[0][lose].0 ^ children[1][lose].0,
+            bits: [
+                children[0][0].1 ^ children[1][0].1 ^ (keep == 0),
+                children[0][1].1 ^ children[1][1].1 ^ (keep == 1),
+            ],
+        };
+        for b in 0..2 {
+            let (seed, control) = children[b][keep];
+            seeds[b] = seed ^ if controls[b] { word.seed } else { 0 };
+            controls[b] = control ^ (controls[b] && word.bits[keep]);
+        }
+        words.push(word);
+    }
+    let mut output = beta.to_vec();
+    xor(&mut output, &convert(seeds[0], beta.len()));
+    xor(&mut output, &convert(seeds[1], beta.len()));
+    [0, 1].map(|b| Key {
+        seed: roots[b],
+        party: b == 1,
+        words: words.clone(),
+        output: output.clone(),
+    })
+}
+
+impl Key {
+    /// bits of the domain
+    pub fn bits(&self) -> u32 {
+        self.words.len() as u32
+    }
+
+    /// walks from a node to its child
+    fn step(&self, level: usize, (seed, control): (Seed, bool), bit: usize) -> (Seed, bool) {
+        let (child, bit_control) = prg(seed)[bit];
+        if control {
+            let word = &self.words[level];
+            (child ^ word.seed, bit_control ^ word.bits[bit])
+        } else {
+            (child, bit_control)
+        }
+    }
+
+    /// output share of a leaf
+    fn leaf(&self, (seed, control): (Seed, bool)) -> Vec<u8> {
+        let mut output = convert(seed, self.output.len());
+        if control {
+            xor(&mut output, &self.output);
+        }
+        output
+    }
+
+    /// evaluates the share at one point
+    pub fn eval(&self, x: usize) -> Vec<u8> {
+        let mut node = (self.seed, self.party);
+        for level in 0..self.words.len() {
+            node = self.step(level, node, x >> (self.words.len() - 1 - level) & 1);
+        }
+        self.leaf(node)
+    }
+
+    /// expands the tree to all leaves in order
+    fn leaves(&self) -> Vec<(Seed, bool)> {
+        let mut nodes = vec![(self.seed, self.party)];
+        for level in 0..self.words.len() {
+            nodes = nodes
+                .into_iter()
+                .flat_map(|node| [self.step(level, node, 0), self.step(level, node, 1)])
+                .collect();
+        }
+        nodes
+    }
+
+    /// evaluates the share on the whole domain
+    pub fn eval_all(&self) -> Vec<Vec<u8>> {
+        self.leaves()
+            .into_iter()
+            .map(|leaf| self.leaf(leaf))
+            .collect()
+    }
+
+    /// evaluates the share of the indicator of `alpha` on the whole domain,
+    /// which is one exactly at `alpha` after XOR of both parties
+    pub fn indicator(&self) -> Vec<bool> {
+        self.leaves()
+            .into_iter()
+            .map(|(_, control)| control)
+            .collect()
+    }
+}
+
+/// stores the bits and party, the root seed, a seed and control bits per level,
+/// and then the output correction
+impl StoreValue for Key {
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = vec![self.words.len() as u8, self.party as u8];
+        bytes.extend(self.seed.to_le_bytes());
+        for word in &self.words {
+            bytes.extend(word.seed.to_le_bytes());
+            bytes.push(word.bits[0] as u8 | (word.bits[1] as u8) << 1);
+        }
+        bytes.extend(self.output);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let (&[bits, party], rest) = bytes.split_first_chunk::<2>().ok_or(ParseError)?;
+        let (seed, mut rest) = rest.split_first_chunk::<16>().ok_or(ParseError)?;
+        if bits > 32 || party > 1 {
+            return Err(ParseError);
+        }
+        let mut words = Vec::with_capacity(bits as usize);
+        for _ in 0..bits {
+            let (word, tail) = rest.split_first_chunk::<17>().ok_or(ParseError)?;
+            let (seed, control) = (&word[..16], word[16]);
+            if control > 3 {
+                return Err(ParseError);
+            }
+            words.push(CorrectionWord {
+                seed: Seed::from_le_bytes(seed.try_into().unwrap()),
+                bits: [control & 1 == 1, control & 2 == 2],
+            });
+            rest = tail;
+        }
+        Ok(Self {
+            seed: Seed::from_le_bytes(*seed),
+            party: party == 1,
+            words,
+            output: rest.to_vec(),
+        })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    let beta = b"point function".to_vec();
+    let [k0, k1] = generate(300, 10, &beta);
+    assert_eq!(10, k0.bits());
+    let zero = vec![0u8; beta.len()];
+    for x in [0, 299, 300, 301, 1023] {
+        let mut y = k0.eval(x);
+        xor(&mut y, &k1.eval(x));
+        assert_eq!(if x == 300 { &beta } else { &zero }, &y);
+    }
+    let (all0, all1) = (k0.eval_all(), k1.eval_all());
+    assert_eq!(1024, all0.len());
+    assert_eq!(k0.eval(777), all0[777]);
+    let points: Vec<usize> = (0..1024).filter(|&x| all0[x] != all1[x]).collect();
+    assert_eq!(vec![300], points);
+    let indicator: Vec<usize> = k0
+        .indicator()
+        .iter()
+        .zip(k1.indicator())
+        .enumerate()
+        .filter_map(|(x, (a, b))| (a ^ b).then_some(x))
+        .collect();
+    assert_eq!(vec![300], indicator);
+
+    let bytes = k1.clone().to_bytes();
+    assert_eq!(2 + 16 + 10 * 17 + beta.len(), bytes.len());
+    assert_eq!(k1, Key::from_bytes(&bytes).unwrap());
+    assert!(Key::from_bytes(&bytes[..100]).is_err());
+    let [k0, k1] = generate(0, 0, &[]);
+    assert!(k0.indicator()[0] ^ k1.indicator()[0]);
+}
@@ -13,6 +13,11 @@ pub enum Error {
 
 pub enum MPCErrorKind {
     InsufficientShares,
+    MalformedMessage,
+    InvalidShare,
+    PolicyViolation,
+    BudgetExceeded,
+    FieldTooSmall,
 }
 
 impl From<std::io::Error> for Error {
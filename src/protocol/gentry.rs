@@ -0,0 +1,53 @@
+//! homomorphic encryption schemes
+//! Fully Homomorphic Encryption Using Ideal Lattices
+//! https://dl.acm.org/doi/10.1145/1536414.1536440
+//! Additively homomorphic schemes let an untrusted server aggregate
+//! ciphertexts that only the key owner can decrypt.
+
+use crate::protocol::error::Result;
+
+/// additively homomorphic public-key encryption of integers,
+/// where anyone with the public key computes on ciphertexts
+pub trait AdditiveHE {
+    type PublicKey;
+    type SecretKey;
+    type Ciphertext: Clone;
+
+    fn generate(&self) -> (Self::SecretKey, Self::PublicKey);
+    fn encrypt(&self, public: &Self::PublicKey, message: u64) -> Self::Ciphertext;
+    /// decrypts a ciphertext, or returns None if the message is out of range
+    fn decrypt(&self, secret: &Self::SecretKey, cipher: &Self::Ciphertext) -> Option<u64>;
+    /// computes a ciphertext of the sum of the messages,
+    /// or fails if a ciphertext is not valid under the public key
+    fn add(
+        &self,
+        public: &Self::PublicKey,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext>;
+    /// computes a ciphertext of the message multiplied by `scalar`,
+    /// or fails if the ciphertext is not valid under the public key
+    fn mul(
+        &self,
+        public: &Self::PublicKey,
+        cipher: &Self::Ciphertext,
+        scalar: u64,
+    ) -> Result<Self::Ciphertext>;
+
+    /// sums ciphertexts, which is an encryption of zero if there are none
+    fn sum(
+        &self,
+        public: &Self::PublicKey,
+        ciphers: &[Self::Ciphertext],
+    ) -> Result<Self::Ciphertext> {
+        ciphers
+            .iter()
+            .try_fold(self.encrypt(public, 0), |acc, cipher| {
+                self.add(public, &acc, cipher)
+            })
+    }
+}
+
+pub mod bfv;
+pub mod elgamal;
+pub mod paillier;
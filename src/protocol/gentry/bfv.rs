@@ -0,0 +1,347 @@
+//! implements the BFV leveled homomorphic encryption scheme
+//! Somewhat Practical Fully Homomorphic Encryption
+//! https://eprint.iacr.org/2012/144.pdf
+//! Ciphertexts are polynomials of `Z_q[X] / (X^n + 1)` and plaintexts are n
+//! slots modulo a prime `t = 1 mod 2n`, packed into a polynomial by the inverse
+//! transform modulo t, so that ring operations act on the slots elementwise.
+
+use crate::math::fourier::{ntt_primes, prime, value, ExactMultiplier, Transform};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::math::gauss::Uint;
+use crate::math::PrimeField;
+
+/// polynomial given by its n coefficients
+pub type Poly = Vec<PrimeField<1>>;
+
+/// parameters with a ring dimension n, a ciphertext modulus q and a plaintext modulus t
+#[derive(Debug, Clone)]
+pub struct Bfv {
+    q: Transform,
+    t: Transform,
+    exact: ExactMultiplier,
+    /// `floor(q / t)`, which scales messages into the top bits
+    delta: u64,
+    /// bits of the digits decomposing ciphertexts in relinearization
+    base: u32,
+}
+
+/// ternary secret `s`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretKey(Poly);
+
+/// encryption of zero `(-(a s + e), a)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey([Poly; 2]);
+
+/// encryptions of `w^i s^2` for the digit base `w`, which turn products back into
+/// ciphertexts of two components
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelinKey(Vec<[Poly; 2]>);
+
+/// ciphertext `(c_0, c_1, ...)` decrypting to `c_0 + c_1 s + c_2 s^2 + ...`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ciphertext(Vec<Poly>);
+
+impl Ciphertext {
+    /// number of components, which is 3 after multiplication until relinearization
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// samples from the centered binomial distribution over `[-2, 2]`
+fn noise() -> i64 {
+    let bits = OsRng.next_u32();
+    (bits & 1) as i64 + (bits >> 1 & 1) as i64 - (bits >> 2 & 1) as i64 - (bits >> 3 & 1) as i64
+}
+
+/// samples uniformly from `{-1, 0, 1}`
+fn ternary() -> i64 {
+    (OsRng.next_u32() % 3) as i64 - 1
+}
+
+impl Bfv {
+    /// constructs parameters of dimension n, a power of two, with the largest NTT-friendly
+    /// primes below `2^q_bits` and `2^t_bits`; the noise grows by about `t_bits + log2(n)`
+    /// bits per multiplication, which bounds the depth of the circuits
+    pub fn new(n: usize, q_bits: u32, t_bits: u32) -> Self {
+        // products of centered coefficients stay within the exact multiplier
+        assert!(
+            q_bits <= 56 && n <= 4096 && t_bits < q_bits,
+            "the parameters must have q of at most 56 bits and n of at most 4096"
+        );
+        let (q, t) = (ntt_primes(q_bits, n, 1)[0], ntt_primes(t_bits, n, 1)[0]);
+        Self {
+            q: Transform::new(q, n),
+            t: Transform::new(t, n),
+            exact: ExactMultiplier::new(n),
+            delta: prime(&q) / prime(&t),
+            base: 16,
+        }
+    }
+
+    /// number of plaintext slots
+    pub fn slots(&self) -> usize {
+        self.q.len()
+    }
+
+    /// plaintext modulus
+    pub fn plain_modulus(&self) -> u64 {
+        prime(&self.t.modulus())
+    }
+
+    /// ciphertext modulus
+    pub fn cipher_modulus(&self) -> u64 {
+        prime(&self.q.modulus())
+    }
+
+    /// embeds a signed integer modulo q
+    fn embed(&self, x: i64) -> PrimeField<1> {
+        let y = self.q.modulus().make(&Uint::from_u64(x.unsigned_abs()));
+        if x < 0 {
+            -y
+        } else {
+            y
+        }
+    }
+
+    /// lifts a coefficient modulo q to `(-q/2, q/2]`
+    fn center(&self, x: &PrimeField<1>) -> i64 {
+        let (x, q) = (value(x), self.cipher_modulus());
+        if x > q / 2 {
+            x as i64 - q as i64
+        } else {
+            x as i64
+        }
+    }
+
+    fn sample(&self, distribution: fn() -> i64) -> Poly {
+        (0..self.slots())
+            .map(|_| self.embed(distribution()))
+            .collect()
+    }
+
+    fn uniform(&self) -> Poly {
+        (0..self.slots())
+            .map(|_| self.q.modulus().random_make())
+            .collect()
+    }
+
+    fn add_poly(a: &[PrimeField<1>], b: &[PrimeField<1>]) -> Poly {
+        a.iter().zip(b).map(|(a, b)| *a + b).collect()
+    }
+
+    /// encryption of zero under `(b, a)` with fresh noise
+    fn zero(&self, b: &[PrimeField<1>], a: &[PrimeField<1>]) -> [Poly; 2] {
+        let u = self.sample(ternary);
+        [
+            Self::add_poly(&self.q.multiply(b, &u), &self.sample(noise)),
+            Self::add_poly(&self.q.multiply(a, &u), &self.sample(noise)),
+        ]
+    }
+
+    /// `(-(a s + e) + m, a)` for a random `a`
+    fn key_switching(&self, secret: &SecretKey, m: &[PrimeField<1>]) -> [Poly; 2] {
+        let a = self.uniform();
+        let b = Self::add_poly(&self.q.multiply(&a, &secret.0), &self.sample(noise))
+            .into_iter()
+            .zip(m)
+            .map(|(b, m)| *m - b)
+            .collect();
+        [b, a]
+    }
+
+    pub fn generate(&self) -> (SecretKey, PublicKey) {
+        let secret = SecretKey(self.sample(ternary));
+        let zero = vec![self.embed(0); self.slots()];
+        let public = PublicKey(self.key_switching(&secret, &zero));
+        (secret, public)
+    }
+
+    /// generates the key relinearizing products under the secret key
+    pub fn relin_key(&self, secret: &SecretKey) -> RelinKey {
+        let square = self.q.multiply(&secret.0, &secret.0);
+        let digits = 64 - self.cipher_modulus().leading_zeros();
+        let scale = self.embed(1 << self.base);
+        let mut power = self.embed(1);
+        let keys = (0..digits.div_ceil(self.base))
+            .map(|_| {
+                let m: Poly = square.iter().map(|s| *s * power).collect();
+                power *= scale;
+                self.key_switching(secret, &m)
+            })
+            .collect();
+        RelinKey(keys)
+    }
+
+    /// encrypts up to n slots modulo t, where missing slots are zero
+    pub fn encrypt(&self, public: &PublicKey, slots: &[u64]) -> Ciphertext {
+        assert!(slots.len() <= self.slots());
+        let t = self.t.modulus();
+        let mut m: Poly = (0..self.slots())
+            .map(|i| t.make(&Uint::from_u64(slots.get(i).copied().unwrap_or(0))))
+            .collect();
+        self.t.inverse(&mut m);
+        let [c0, c1] = self.zero(&public.0[0], &public.0[1]);
+        let delta = self.embed(self.delta as i64);
+        let c0 = c0
+            .iter()
+            .zip(&m)
+            .map(|(c, m)| *c + self.embed(value(m) as i64) * delta)
+            .collect();
+        Ciphertext(vec![c0, c1])
+    }
+
+    /// computes `c_0 + c_1 s + c_2 s^2 + ...`, which is `delta m + v` for the noise `v`
+    fn phase(&self, secret: &SecretKey, cipher: &Ciphertext) -> Poly {
+        let mut power = secret.0.clone();
+        let mut phase = cipher.0[0].clone();
+        for (i, c) in cipher.0.iter().enumerate().skip(1) {
+            if i > 1 {
+                power = self.q.multiply(&power, &secret.0);
+            }
+            phase = Self::add_poly(&phase, &self.q.multiply(c, &power));
+        }
+        phase
+    }
+
+    /// decrypts all n slots
+    pub fn decrypt(&self, secret: &SecretKey, cipher: &Ciphertext) -> Vec<u64> {
+        let (q, t) = (self.cipher_modulus() as u128, self.plain_modulus() as u128);
+        let modulus = self.t.modulus();
+        let mut m: Poly = self
+            .phase(secret, cipher)
+            .iter()
+            .map(|x| {
+                let m = (t * value(x) as u128 + q / 2) / q % t;
+                modulus.make(&Uint::from_u64(m as u64))
+            })
+            .collect();
+        self.t.forward(&mut m);
+        m.iter().map(value).collect()
+    }
+
+    /// gets the bits of noise left before decryption fails, or 0 if it already fails
+    pub fn noise_budget(&self, secret: &SecretKey, cipher: &Ciphertext) -> u32 {
+        let (q, t) = (self.cipher_modulus() as u128, self.plain_modulus() as u128);
+        let noise = self
+            .phase(secret, cipher)
+            .iter()
+            .map(|x| {
+                // the noise is the distance from the nearest multiple of q / t
+                let scaled = t * value(x) as u128 % q;
+                scaled.min(q - scaled)
+            })
+            .max()
+            .unwrap_or(0);
+        // decryption rounds correctly while t v < q / 2
+        let bound = (q / 2).ilog2();
+        bound.saturating_sub(noise.max(1).ilog2() + 1)
+    }
+
+    /// adds ciphertexts slotwise
+    pub fn add(&self, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
+        let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+        let mut sum = long.clone();
+        for (s, c) in sum.0.iter_mut().zip(&short.0) {
+            *s = Self::add_poly(s, c);
+        }
+        sum
+    }
+
+    /// multiplies ciphertexts of two components slotwise into a ciphertext of three
+    pub fn mul(&self, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
+        assert!(
+            a.len() == 2 && b.len() == 2,
+            "relinearize before multiplying"
+        );
+        let lift = |c: &Poly| -> Vec<i64> { c.iter().map(|x| self.center(x)).collect() };
+        let (a0, a1, b0, b1) = (lift(&a.0[0]), lift(&a.0[1]), lift(&b.0[0]), lift(&b.0[1]));
+        let (q, t) = (self.cipher_modulus() as i128, self.plain_modulus() as i128);
+        // rounds `t x / q` for the integer tensor product, with `x = k q + r`
+        let scale = |x: Vec<i128>| -> Poly {
+            x.into_iter()
+                .map(|x| {
+                    let (k, r) = (x.div_euclid(q), x.rem_euclid(q));
+                    let y = (t * k + (t * r + q / 2) / q).rem_euclid(q);
+                    self.q.modulus().make(&Uint::from_u64(y as u64))
+                })
+                .collect()
+        };
+        let cross: Vec<i128> = self
+            .exact
+            .multiply(&a0, &b1)
+            .into_iter()
+            .zip(self.exact.multiply(&a1, &b0))
+            .map(|(x, y)| x + y)
+            .collect();
+        Ciphertext(vec![
+            scale(self.exact.multiply(&a0, &b0)),
+            scale(cross),
+            scale(self.exact.multiply(&a1, &b1)),
+        ])
+    }
+
+    /// turns a product of three components back into two by decomposing
+    /// the last component into digits of the relinearization key base
+    pub fn relinearize(&self, key: &RelinKey, cipher: &Ciphertext) -> Ciphertext {
+        if cipher.len() == 2 {
+            return cipher.clone();
+        }
+        assert_eq!(
+            3,
+            cipher.len(),
+            "only products of two ciphertexts are relinearized"
+        );
+        let [mut c0, mut c1, c2] = [0, 1, 2].map(|i| cipher.0[i].clone());
+        let mask = (1 << self.base) - 1;
+        for (i, [k0, k1]) in key.0.iter().enumerate() {
+            let digit: Poly = c2
+                .iter()
+                .map(|c| self.embed((value(c) >> (i as u32 * self.base) & mask) as i64))
+                .collect();
+            c0 = Self::add_poly(&c0, &self.q.multiply(&digit, k0));
+            c1 = Self::add_poly(&c1, &self.q.multiply(&digit, k1));
+        }
+        Ciphertext(vec![c0, c1])
+    }
+}
+
+#[test]
+fn test_correctness() {
+    let scheme = Bfv::new(256, 56, 13);
+    assert_eq!(7681, scheme.plain_modulus());
+    let (secret, public) = scheme.generate();
+    let relin = scheme.relin_key(&secret);
+    let t = scheme.plain_modulus();
+    let xs: Vec<u64> = (0..256).map(|i| i * 29 % t).collect();
+    let ys: Vec<u64> = (0..256).map(|i| (t - 1 - i * i % t) % t).collect();
+    let (a, b) = (scheme.encrypt(&public, &xs), scheme.encrypt(&public, &ys));
+    assert_eq!(xs, scheme.decrypt(&secret, &a));
+    let fresh = scheme.noise_budget(&secret, &a);
+    assert!(fresh > 20);
+
+    let sum = scheme.add(&a, &b);
+    let expected: Vec<u64> = xs.iter().zip(&ys).map(|(x, y)| (x + y) % t).collect();
+    assert_eq!(expected, scheme.decrypt(&secret, &sum));
+
+    let product = scheme.mul(&a, &b);
+    assert_eq!(3, product.len());
+    let expected: Vec<u64> = xs.iter().zip(&ys).map(|(x, y)| x * y % t).collect();
+    assert_eq!(expected, scheme.decrypt(&secret, &product));
+    let product = scheme.relinearize(&relin, &product);
+    assert_eq!(2, product.len());
+    assert_eq!(expected, scheme.decrypt(&secret, &product));
+    let budget = scheme.noise_budget(&secret, &product);
+    assert!(0 < budget && budget < fresh);
+
+    // a second multiplication consumes the rest of the budget
+    let square = scheme.relinearize(&relin, &scheme.mul(&product, &product));
+    assert!(scheme.noise_budget(&secret, &square) < budget);
+    let partial = scheme.encrypt(&public, &[3, 4]);
+    assert_eq!([3, 4, 0], scheme.decrypt(&secret, &partial)[..3]);
+}
@@ -0,0 +1,120 @@
+//! implements exponential ElGamal encryption over secp256k1
+//! A Public Key Cryptosystem and a Signature Scheme Based on Discrete Logarithms
+//! https://link.springer.com/chapter/10.1007/3-540-39568-7_2
+//! A message `m` is encrypted as the point `m * G`, so that ciphertexts add up,
+//! and decryption solves a discrete logarithm of bounded size.
+//! Ciphertexts are those of `threshold::elgamal`, so a sum encrypted to a
+//! jointly generated key can be decrypted by key holders and passed to `log`.
+
+use super::AdditiveHE;
+use crate::math::weierstrass::encode_point;
+use crate::protocol::error::Result;
+use crate::protocol::threshold::elgamal::{encrypt, Ciphertext};
+use k256::{
+    elliptic_curve::{ops::MulByGenerator, rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+use std::collections::HashMap;
+
+/// encrypts messages of at most `bits` bits, whose logarithms are solved by
+/// baby-step giant-step with a table of `2^(bits / 2)` points
+pub struct ElGamal {
+    /// baby steps `j * G` to `j`
+    table: HashMap<Vec<u8>, u64>,
+    /// number of baby steps
+    steps: u64,
+    /// number of giant steps
+    giants: u64,
+}
+
+impl ElGamal {
+    pub fn new(bits: u32) -> Self {
+        assert!(bits <= 64, "messages are at most 64 bits");
+        let steps = 1u64 << bits.div_ceil(2);
+        let giants = if bits == 64 {
+            u64::MAX
+        } else {
+            (1u64 << bits) - 1
+        } / steps
+            + 1;
+        let mut table = HashMap::with_capacity(steps as usize);
+        let mut point = ProjectivePoint::IDENTITY;
+        for j in 0..steps {
+            table.insert(encode_point(&point), j);
+            point += ProjectivePoint::GENERATOR;
+        }
+        Self {
+            table,
+            steps,
+            giants,
+        }
+    }
+
+    /// solves `m` from `m * G`, or returns None if `m` is out of range
+    pub fn log(&self, point: &ProjectivePoint) -> Option<u64> {
+        let giant = -ProjectivePoint::mul_by_generator(&Scalar::from(self.steps));
+        let mut point = *point;
+        for i in 0..self.giants {
+            if let Some(j) = self.table.get(&encode_point(&point)) {
+                return Some(i * self.steps + j);
+            }
+            point += giant;
+        }
+        None
+    }
+}
+
+impl AdditiveHE for ElGamal {
+    type PublicKey = ProjectivePoint;
+    type SecretKey = Scalar;
+    type Ciphertext = Ciphertext;
+
+    fn generate(&self) -> (Scalar, ProjectivePoint) {
+        let secret = Scalar::random(&mut OsRng);
+        (secret, ProjectivePoint::mul_by_generator(&secret))
+    }
+
+    fn encrypt(&self, public: &ProjectivePoint, message: u64) -> Ciphertext {
+        encrypt(
+            public,
+            &ProjectivePoint::mul_by_generator(&Scalar::from(message)),
+        )
+    }
+
+    fn decrypt(&self, secret: &Scalar, cipher: &Ciphertext) -> Option<u64> {
+        self.log(&(cipher.body - cipher.ephemeral * secret))
+    }
+
+    fn add(&self, _: &ProjectivePoint, a: &Ciphertext, b: &Ciphertext) -> Result<Ciphertext> {
+        Ok(Ciphertext {
+            ephemeral: a.ephemeral + b.ephemeral,
+            body: a.body + b.body,
+        })
+    }
+
+    fn mul(&self, _: &ProjectivePoint, cipher: &Ciphertext, scalar: u64) -> Result<Ciphertext> {
+        let scalar = Scalar::from(scalar);
+        Ok(Ciphertext {
+            ephemeral: cipher.ephemeral * scalar,
+            body: cipher.body * scalar,
+        })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    let scheme = ElGamal::new(20);
+    let (secret, public) = scheme.generate();
+    let values = [3u64, 1000, 70000, 0, 25];
+    let ciphers: Vec<_> = values.iter().map(|&v| scheme.encrypt(&public, v)).collect();
+    assert_eq!(Some(70000), scheme.decrypt(&secret, &ciphers[2]));
+    // the server sums and scales without the secret key
+    let sum = scheme.sum(&public, &ciphers).unwrap();
+    assert_eq!(Some(71028), scheme.decrypt(&secret, &sum));
+    let scaled = scheme.mul(&public, &ciphers[1], 7).unwrap();
+    assert_eq!(Some(7000), scheme.decrypt(&secret, &scaled));
+    let large = scheme.mul(&public, &ciphers[2], 16).unwrap();
+    assert_eq!(None, scheme.decrypt(&secret, &large));
+    let (other, _) = scheme.generate();
+    assert_ne!(Some(25), scheme.decrypt(&other, &ciphers[4]));
+}
@@ -0,0 +1,255 @@
+//! implements Paillier encryption
+//! Public-Key Cryptosystems Based on Composite Degree Residuosity Classes
+//! https://link.springer.com/chapter/10.1007/3-540-48910-X_16
+//! A message `m` is encrypted as `(1 + n)^m * r^n` in the unit group modulo
+//! `n^2`, where the modulus `n` has LIMBS limbs and its square has WIDE limbs.
+
+use super::AdditiveHE;
+use crate::math::abel::{AbelianMonoid, Inv, Modulus, UnitGroup};
+use crate::math::galois::{decode_uint, encode_uint};
+use crate::math::gauss::{NonZero, Odd, Uint};
+use crate::math::PrimeModulus;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::store::{ParseError, StoreValue};
+
+/// generates keys of a modulus of `bits` bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Paillier<const LIMBS: usize, const WIDE: usize> {
+    bits: u32,
+}
+
+/// public key of modulus `n`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey<const LIMBS: usize, const WIDE: usize> {
+    n: Uint<LIMBS>,
+    square: Modulus<WIDE>,
+}
+
+/// secret key with `phi = (p - 1)(q - 1)` and its inverse `mu` modulo `n`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecretKey<const LIMBS: usize, const WIDE: usize> {
+    phi: Uint<LIMBS>,
+    mu: Uint<LIMBS>,
+    public: PublicKey<LIMBS, WIDE>,
+}
+
+/// ciphertext as an integer modulo `n^2`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ciphertext<const WIDE: usize>(Uint<WIDE>);
+
+impl<const LIMBS: usize, const WIDE: usize> Paillier<LIMBS, WIDE> {
+    pub fn new(bits: u32) -> Self {
+        assert!(
+            bits.is_multiple_of(2) && bits as usize <= 64 * LIMBS && 2 * LIMBS <= WIDE,
+            "the modulus must have an even number of bits fitting into LIMBS"
+        );
+        Self { bits }
+    }
+}
+
+impl<const LIMBS: usize, const WIDE: usize> PublicKey<LIMBS, WIDE> {
+    /// gets the modulus `n`, which bounds the messages
+    pub fn modulus(&self) -> Uint<LIMBS> {
+        self.n
+    }
+}
+
+impl<const LIMBS: usize, const WIDE: usize> SecretKey<LIMBS, WIDE> {
+    pub fn public(&self) -> &PublicKey<LIMBS, WIDE> {
+        &self.public
+    }
+}
+
+macro_rules! impl_paillier {
+    ($($limbs:literal $wide:literal)+) => {$(
+        impl PublicKey<$limbs, $wide> {
+            /// constructs the public key of modulus `n`
+            fn from_modulus(n: Uint<$limbs>) -> Option<Self> {
+                let square: Uint<$wide> = n.widening_mul(&n);
+                Some(Self {
+                    n,
+                    square: Modulus::from::<{ 2 * $wide }>(Option::from(square.to_odd())?),
+                })
+            }
+
+            /// maps a ciphertext into the unit group modulo `n^2`,
+            /// or fails if it is out of range or shares a factor with `n`
+            fn unit(&self, cipher: &Ciphertext<$wide>) -> Result<UnitGroup<$wide>> {
+                let square: Uint<$wide> = self.n.widening_mul(&self.n);
+                if cipher.0 >= square {
+                    return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+                }
+                self.square
+                    .make(&cipher.0)
+                    .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))
+            }
+        }
+
+        impl SecretKey<$limbs, $wide> {
+            /// constructs the secret key of modulus `n` with `phi(n)`
+            fn from_phi(n: Uint<$limbs>, phi: Uint<$limbs>) -> Option<Self> {
+                let public = PublicKey::<$limbs, $wide>::from_modulus(n)?;
+                let mu = Modulus::from::<$wide>(Option::from(n.to_odd())?)
+                    .make(&phi)?
+                    .inv()
+                    .rep();
+                Some(Self { phi, mu, public })
+            }
+        }
+
+        impl Paillier<$limbs, $wide> {
+            /// encrypts a message modulo `n`
+            pub fn encrypt_uint(
+                &self,
+                public: &PublicKey<$limbs, $wide>,
+                message: &Uint<$limbs>,
+            ) -> Ciphertext<$wide> {
+                let n: Uint<$wide> = public.n.resize();
+                let g = public
+                    .square
+                    .make(&message.widening_mul(&public.n).wrapping_add(&Uint::ONE))
+                    .unwrap();
+                Ciphertext((g * public.square.random_make().pow(&n)).rep())
+            }
+
+            /// decrypts a message modulo `n`
+            pub fn decrypt_uint(
+                &self,
+                secret: &SecretKey<$limbs, $wide>,
+                cipher: &Ciphertext<$wide>,
+            ) -> Option<Uint<$limbs>> {
+                let public = &secret.public;
+                let u = public.square.make(&cipher.0)?.pow(&secret.phi.resize()).rep();
+                let n: NonZero<Uint<$limbs>> = Option::from(NonZero::new(public.n))?;
+                let wide = NonZero::new(public.n.resize::<$wide>()).unwrap();
+                let (l, _) = u.wrapping_sub(&Uint::ONE).div_rem(&wide);
+                Some(l.resize::<$limbs>().mul_mod_vartime(&secret.mu, &n))
+            }
+        }
+
+        impl AdditiveHE for Paillier<$limbs, $wide> {
+            type PublicKey = PublicKey<$limbs, $wide>;
+            type SecretKey = SecretKey<$limbs, $wide>;
+            type Ciphertext = Ciphertext<$wide>;
+
+            fn generate(&self) -> (Self::SecretKey, Self::PublicKey) {
+                loop {
+                    let prime = || Odd::from(PrimeModulus::<$limbs>::from_random(self.bits / 2)).get();
+                    let (p, q) = (prime(), prime());
+                    if p == q {
+                        continue;
+                    }
+                    let n = p.wrapping_mul(&q);
+                    let phi = (p - Uint::ONE).wrapping_mul(&(q - Uint::ONE));
+                    if let Some(secret) = SecretKey::<$limbs, $wide>::from_phi(n, phi) {
+                        return (secret, secret.public);
+                    }
+                }
+            }
+
+            fn encrypt(&self, public: &Self::PublicKey, message: u64) -> Self::Ciphertext {
+                self.encrypt_uint(public, &Uint::from_u64(message))
+            }
+
+            fn decrypt(&self, secret: &Self::SecretKey, cipher: &Self::Ciphertext) -> Option<u64> {
+                let message = self.decrypt_uint(secret, cipher)?;
+                (message.bits() <= 64).then(|| message.as_words()[0] as u64)
+            }
+
+            fn add(
+                &self,
+                public: &Self::PublicKey,
+                a: &Self::Ciphertext,
+                b: &Self::Ciphertext,
+            ) -> Result<Self::Ciphertext> {
+                Ok(Ciphertext((public.unit(a)? * public.unit(b)?).rep()))
+            }
+
+            fn mul(
+                &self,
+                public: &Self::PublicKey,
+                cipher: &Self::Ciphertext,
+                scalar: u64,
+            ) -> Result<Self::Ciphertext> {
+                Ok(Ciphertext(public.unit(cipher)?.pow(&Uint::from_u64(scalar)).rep()))
+            }
+        }
+
+        /// stores the modulus `n`
+        impl StoreValue for PublicKey<$limbs, $wide> {
+            fn to_bytes(self) -> Vec<u8> {
+                encode_uint(&self.n)
+            }
+
+            fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, ParseError> {
+                decode_uint(bytes).and_then(Self::from_modulus).ok_or(ParseError)
+            }
+        }
+
+        /// stores the modulus `n` followed by `phi(n)`
+        impl StoreValue for SecretKey<$limbs, $wide> {
+            fn to_bytes(self) -> Vec<u8> {
+                [encode_uint(&self.public.n), encode_uint(&self.phi)].concat()
+            }
+
+            fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, ParseError> {
+                let (n, phi) = bytes.split_at_checked(bytes.len() / 2).ok_or(ParseError)?;
+                let (n, phi) = (decode_uint(n).ok_or(ParseError)?, decode_uint(phi).ok_or(ParseError)?);
+                Self::from_phi(n, phi).ok_or(ParseError)
+            }
+        }
+
+        impl StoreValue for Ciphertext<$wide> {
+            fn to_bytes(self) -> Vec<u8> {
+                encode_uint(&self.0)
+            }
+
+            fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, ParseError> {
+                decode_uint(bytes).map(Self).ok_or(ParseError)
+            }
+        }
+    )+};
+}
+
+impl_paillier!(1 2 2 4 4 8 8 16 16 32 32 64);
+
+#[test]
+fn test_correctness() {
+    use crate::store::{disk::DiskStore, Store};
+
+    let scheme = Paillier::<2, 4>::new(128);
+    let (secret, public) = scheme.generate();
+    let values = [3u64, u64::MAX / 4, 70000, 0];
+    let ciphers: Vec<_> = values.iter().map(|&v| scheme.encrypt(&public, v)).collect();
+    assert_ne!(ciphers[3], scheme.encrypt(&public, 0));
+    assert_eq!(Some(70000), scheme.decrypt(&secret, &ciphers[2]));
+    let sum = scheme.sum(&public, &ciphers).unwrap();
+    assert_eq!(Some(u64::MAX / 4 + 70003), scheme.decrypt(&secret, &sum));
+    let scaled = scheme.mul(&public, &ciphers[1], 4).unwrap();
+    assert_eq!(Some(u64::MAX - 3), scheme.decrypt(&secret, &scaled));
+    let large = scheme.mul(&public, &ciphers[1], 5).unwrap();
+    assert_eq!(None, scheme.decrypt(&secret, &large));
+    assert_eq!(
+        Some(Uint::<2>::from_u64(u64::MAX / 4).wrapping_mul(&Uint::<1>::from_u64(5))),
+        scheme.decrypt_uint(&secret, &large)
+    );
+    // ciphertexts sharing a factor with n or out of range are rejected
+    let n = Ciphertext(public.modulus().resize::<4>());
+    let wide = Ciphertext(public.modulus().widening_mul(&public.modulus()));
+    assert!(scheme.add(&public, &ciphers[0], &n).is_err());
+    assert!(scheme.mul(&public, &wide, 2).is_err());
+    assert!(scheme
+        .sum(&public, &[ciphers[0], Ciphertext(Uint::ZERO)])
+        .is_err());
+
+    // keys and ciphertexts are stored and loaded by the key owner
+    let mut store = DiskStore::new("data/Alice", "paillier").unwrap();
+    store.set("secret", &secret).unwrap();
+    store.set("public", &public).unwrap();
+    store.set("sum", &sum).unwrap();
+    let secret: SecretKey<2, 4> = store.get("secret").unwrap();
+    let public: PublicKey<2, 4> = store.get("public").unwrap();
+    let sum: Ciphertext<4> = store.get("sum").unwrap();
+    assert_eq!(&public, secret.public());
+    assert_eq!(Some(u64::MAX / 4 + 70003), scheme.decrypt(&secret, &sum));
+}
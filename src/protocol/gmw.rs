@@ -0,0 +1,236 @@
+//! implements the GMW protocol on XOR shares of bits among N parties
+//! How to Play Any Mental Game
+//! https://dl.acm.org/doi/10.1145/28395.28420
+//! AND gates consume Beaver bit triples, which are generated by pairwise OT
+//! extension beforehand, so that the circuit is evaluated in one round per AND-depth.
+
+use super::circuit::{Circuit, Gate};
+use super::error::{Error, MPCErrorKind, Result};
+use super::ot::{iknp03, RandomOTReceive, RandomOTSend};
+use super::party::{Channel, MultiParty};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+
+/// shares of a random multiplication triple `c = a & b`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Triple {
+    pub a: bool,
+    pub b: bool,
+    pub c: bool,
+}
+
+/// packs bits into bytes, least significant first
+fn pack(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|byte| byte.iter().rev().fold(0, |acc, &bit| acc << 1 | bit as u8))
+        .collect()
+}
+
+/// unpacks `count` bits from bytes
+fn unpack(bytes: &[u8], count: usize) -> Result<Vec<bool>> {
+    if bytes.len() != count.div_ceil(8) {
+        return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+    }
+    Ok((0..count)
+        .map(|i| bytes[i / 8] >> (i % 8) & 1 == 1)
+        .collect())
+}
+
+/// samples random bits
+fn random_bits(count: usize) -> Vec<bool> {
+    (0..count).map(|_| OsRng.next_u32() & 1 == 1).collect()
+}
+
+/// evaluates boolean circuits on XOR shares among N parties,
+/// which is secure against N - 1 semi-honest parties
+pub struct Evaluator<const N: usize> {
+    party: MultiParty<N>,
+}
+
+impl<const N: usize> Evaluator<N> {
+    pub fn new(party: MultiParty<N>) -> Self {
+        Self { party }
+    }
+
+    /// shares `bits` of party `owner` to all parties, where other parties pass
+    /// no bits but the number of bits in `count`
+    pub fn input(&self, owner: usize, bits: &[bool], count: usize) -> Result<Vec<bool>> {
+        if owner != self.party.id {
+            return unpack(&self.party.recv(&owner)?, count);
+        }
+        let mut shares = bits.to_vec();
+        for id in (0..N).filter(|&id| id != owner) {
+            let mask = random_bits(bits.len());
+            self.party.send(&id, &pack(&mask))?;
+            shares.iter_mut().zip(mask).for_each(|(s, m)| *s ^= m);
+        }
+        Ok(shares)
+    }
+
+    /// reveals the shared bits to all parties
+    pub fn open(&self, shares: &[bool]) -> Result<Vec<bool>> {
+        self.party.broadcast(&pack(shares))?;
+        let mut bits = shares.to_vec();
+        for id in (0..N).filter(|&id| id != self.party.id) {
+            let other = unpack(&self.party.recv(&id)?, shares.len())?;
+            bits.iter_mut().zip(other).for_each(|(b, o)| *b ^= o);
+        }
+        Ok(bits)
+    }
+
+    /// generates shares of random triples, where the cross terms `a_i & b_j`
+    /// of every pair of parties are shared by random OTs extended by IKNP.
+    /// Each pair of parties runs 128 public-key base OTs in each direction per
+    /// call, so a call should generate all triples of a circuit at once, and
+    /// then each triple costs 16 bytes and a bit in each direction per pair.
+    pub fn triples(&self, count: usize) -> Result<Vec<Triple>> {
+        let me = self.party.id;
+        let (a, b) = (random_bits(count), random_bits(count));
+        let mut c: Vec<bool> = a.iter().zip(&b).map(|(a, b)| a & b).collect();
+        // pairs run in the same order at every party, so that no party waits on a busy peer
+        for id in (0..N).filter(|&id| id != me) {
+            let peer = self.party.peer(id);
+            let (sender, receiver) = (iknp03::Sender(&peer), iknp03::Receiver(&peer));
+            // the sender keeps `m0` and corrects `m1` to `m0 ^ a`, so that the
+            // receiver obtains `m0 ^ (a & b)`
+            let send = |c: &mut Vec<bool>| -> Result<()> {
+                let randoms: Vec<[[u8; 1]; 2]> = sender.send_random(count)?;
+                let corrections: Vec<bool> = randoms
+                    .iter()
+                    .zip(&a)
+                    .map(|([[m0], [m1]], &a)| ((m0 ^ m1) & 1 == 1) ^ a)
+                    .collect();
+                peer.push(&pack(&corrections))?;
+                c.iter_mut()
+                    .zip(randoms)
+                    .for_each(|(c, [[m0], _])| *c ^= m0 & 1 == 1);
+                Ok(())
+            };
+            let receive = |c: &mut Vec<bool>| -> Result<()> {
+                let randoms: Vec<[u8; 1]> = receiver.receive_random(&b)?;
+                let corrections = unpack(&peer.pull()?, count)?;
+                for (((c, [m]), &b), correction) in
+                    c.iter_mut().zip(randoms).zip(&b).zip(corrections)
+                {
+                    *c ^= (m & 1 == 1) ^ (b && correction);
+                }
+                Ok(())
+            };
+            if me < id {
+                send(&mut c)?;
+                receive(&mut c)?;
+            } else {
+                receive(&mut c)?;
+                send(&mut c)?;
+            }
+        }
+        Ok((0..count)
+            .map(|i| Triple {
+                a: a[i],
+                b: b[i],
+                c: c[i],
+            })
+            .collect())
+    }
+
+    /// multiplies shared bits pairwise in one round, consuming one triple each
+    pub fn and(&self, xs: &[bool], ys: &[bool], triples: &[Triple]) -> Result<Vec<bool>> {
+        assert!(xs.len() == ys.len() && ys.len() <= triples.len());
+        let masked: Vec<bool> = xs
+            .iter()
+            .zip(ys)
+            .zip(triples)
+            .flat_map(|((x, y), t)| [x ^ t.a, y ^ t.b])
+            .collect();
+        let opened = self.open(&masked)?;
+        Ok(opened
+            .chunks_exact(2)
+            .zip(triples)
+            .map(|(de, t)| {
+                let (d, e) = (de[0], de[1]);
+                t.c ^ (d & t.b) ^ (e & t.a) ^ (self.party.id == 0 && d & e)
+            })
+            .collect())
+    }
+
+    /// evaluates the circuit, whose input `i` is given by party `i`,
+    /// and reveals the output to all parties
+    pub fn run(&self, circuit: &Circuit, inputs: &[bool]) -> Result<Vec<bool>> {
+        assert_eq!(
+            circuit.inputs.len(),
+            N,
+            "the circuit must have an input per party"
+        );
+        assert_eq!(circuit.inputs[self.party.id], inputs.len());
+        let mut triples = self.triples(circuit.and_count())?.into_iter();
+        let mut shares = vec![false; circuit.wires];
+        for (owner, &width) in circuit.inputs.iter().enumerate() {
+            let bits = self.input(owner, inputs, width)?;
+            shares[circuit.input_wires(owner)].copy_from_slice(&bits);
+        }
+        let leader = self.party.id == 0;
+        for layer in circuit.layers() {
+            let mut ands = vec![];
+            for gate in layer {
+                match gate {
+                    Gate::Xor(a, b, out) => shares[out] = shares[a] ^ shares[b],
+                    Gate::Inv(a, out) => shares[out] = shares[a] ^ leader,
+                    Gate::Eq(bit, out) => shares[out] = bit && leader,
+                    Gate::Eqw(a, out) => shares[out] = shares[a],
+                    Gate::And(a, b, out) => ands.push((a, b, out)),
+                }
+            }
+            if ands.is_empty() {
+                continue;
+            }
+            let xs: Vec<bool> = ands.iter().map(|&(a, _, _)| shares[a]).collect();
+            let ys: Vec<bool> = ands.iter().map(|&(_, b, _)| shares[b]).collect();
+            let used: Vec<Triple> = triples.by_ref().take(ands.len()).collect();
+            for ((_, _, out), z) in ands.into_iter().zip(self.and(&xs, &ys, &used)?) {
+                shares[out] = z;
+            }
+        }
+        self.open(&shares[circuit.output_wires()])
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::circuit::{from_bits, to_bits, Builder};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    // the largest of three values and whether the first two are equal
+    let mut builder = Builder::new(&[8, 8, 8]);
+    let (a, b, c) = (builder.input(0), builder.input(1), builder.input(2));
+    let lt = builder.less_than(&a, &b);
+    let max = builder.mux(lt, &a, &b);
+    let lt = builder.less_than(&max, &c);
+    let max = builder.mux(lt, &max, &c);
+    let eq = builder.equal(&a, &b);
+    let circuit = builder.finish(&[max, vec![eq]]);
+
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9090 + i as u16)));
+    let values = [[42, 200, 7], [9, 9, 1]];
+    let circuit = &circuit;
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                scope.spawn(move || {
+                    let evaluator = Evaluator::new(MultiParty::new(id, &peers).unwrap());
+                    values
+                        .iter()
+                        .map(|values| evaluator.run(circuit, &to_bits(values[id], 8)).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for outputs in results {
+        assert_eq!(200, from_bits(&outputs[0][..8]));
+        assert!(!outputs[0][8]);
+        assert_eq!(9, from_bits(&outputs[1][..8]));
+        assert!(outputs[1][8]);
+    }
+}
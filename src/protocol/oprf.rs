@@ -0,0 +1,213 @@
+//! implements the verifiable 2HashDH oblivious pseudorandom function on secp256k1
+//! Round-Optimal Password-Protected Secret Sharing and T-PAKE in the Password-Only Model
+//! https://eprint.iacr.org/2014/650
+//! The function is `F_k(x) = H2(x, H1(x)^k)`. The client sends `H1(x)^r` for a
+//! random `r`, the server raises it to its key `k`, and the client removes `r`,
+//! so that the server learns nothing about x. A batch is answered with one
+//! Chaum-Pedersen proof on random linear combinations of the inputs and the
+//! outputs, which shows that every output used the key of the public `k G`.
+
+use super::error::{Error, MPCErrorKind, Result};
+use super::party::{Channel, TwoParty};
+use super::zkp::{cp92, Transcript};
+use crate::math::weierstrass::{
+    decode_points, decode_scalar, encode_point, encode_points, hash_to_curve,
+};
+use crate::store::{ParseError, StoreValue};
+use blake2::{Blake2b512, Digest};
+use k256::{
+    elliptic_curve::{ops::MulByGenerator, rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+
+/// output of the pseudorandom function
+pub type Output = [u8; 32];
+
+/// hashes an input and its point to the power of the key into the output
+fn finalize(input: &[u8], point: &ProjectivePoint) -> Output {
+    let hash = Blake2b512::new()
+        .chain_update(b"oprf")
+        .chain_update((input.len() as u64).to_le_bytes())
+        .chain_update(input)
+        .chain_update(encode_point(point))
+        .finalize();
+    hash[..32].try_into().unwrap()
+}
+
+/// combines the inputs and the outputs of a batch with coefficients bound to all of them
+fn combine(
+    transcript: &mut Transcript,
+    public: &ProjectivePoint,
+    inputs: &[ProjectivePoint],
+    outputs: &[ProjectivePoint],
+) -> (ProjectivePoint, ProjectivePoint) {
+    transcript.append_point(b"public", public);
+    for (input, output) in inputs.iter().zip(outputs) {
+        transcript.append_point(b"input", input);
+        transcript.append_point(b"output", output);
+    }
+    inputs.iter().zip(outputs).fold(
+        (ProjectivePoint::IDENTITY, ProjectivePoint::IDENTITY),
+        |(m, z), (input, output)| {
+            let c = transcript.challenge(b"coefficient");
+            (m + *input * c, z + *output * c)
+        },
+    )
+}
+
+fn malformed() -> Error {
+    Error::MPCError(MPCErrorKind::MalformedMessage)
+}
+
+/// key of the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    secret: Scalar,
+}
+
+impl Key {
+    pub fn random() -> Self {
+        Self {
+            secret: Scalar::random(&mut OsRng),
+        }
+    }
+
+    /// commitment `k G` with which clients verify evaluations
+    pub fn public(&self) -> ProjectivePoint {
+        ProjectivePoint::mul_by_generator(&self.secret)
+    }
+
+    /// evaluates the function in the clear, as the server does on its own inputs
+    pub fn evaluate(&self, input: &[u8]) -> Output {
+        finalize(input, &(hash_to_curve(input) * self.secret))
+    }
+}
+
+/// stores the big-endian secret scalar
+impl StoreValue for Key {
+    fn to_bytes(self) -> Vec<u8> {
+        self.secret.to_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> std::result::Result<Self, ParseError> {
+        let secret = decode_scalar(bytes).ok_or(ParseError)?;
+        if bool::from(secret.is_zero()) {
+            return Err(ParseError);
+        }
+        Ok(Self { secret })
+    }
+}
+
+/// holds the key and evaluates blinded inputs
+pub struct Server<C = TwoParty> {
+    party: C,
+    key: Key,
+}
+
+impl<C: Channel> Server<C> {
+    pub fn new(party: C, key: Key) -> Self {
+        Self { party, key }
+    }
+
+    /// answers one batch of blinded inputs with a proof
+    pub fn evaluate(&self) -> Result<()> {
+        let inputs = decode_points(&self.party.pull()?).ok_or_else(malformed)?;
+        let outputs: Vec<ProjectivePoint> = inputs.iter().map(|p| *p * self.key.secret).collect();
+        self.party.push(&encode_points(&outputs))?;
+        if inputs.is_empty() {
+            return Ok(());
+        }
+        let mut transcript = Transcript::new(b"oprf");
+        let (m, _) = combine(&mut transcript, &self.key.public(), &inputs, &outputs);
+        let proof = cp92::prove(
+            &mut transcript,
+            &[ProjectivePoint::GENERATOR, m],
+            &self.key.secret,
+        );
+        self.party.push(&proof.to_bytes())
+    }
+}
+
+/// learns the outputs on its inputs, verified against the public key of the server
+pub struct Client<C = TwoParty> {
+    party: C,
+    public: ProjectivePoint,
+}
+
+impl<C: Channel> Client<C> {
+    pub fn new(party: C, public: ProjectivePoint) -> Self {
+        Self { party, public }
+    }
+
+    /// evaluates the function on a batch of inputs, and fails if the proof does not verify
+    pub fn evaluate(&self, inputs: &[&[u8]]) -> Result<Vec<Output>> {
+        let blinds: Vec<Scalar> = inputs.iter().map(|_| Scalar::random(&mut OsRng)).collect();
+        let blinded: Vec<ProjectivePoint> = inputs
+            .iter()
+            .zip(&blinds)
+            .map(|(input, r)| hash_to_curve(input) * r)
+            .collect();
+        self.party.push(&encode_points(&blinded))?;
+        let evaluated = decode_points(&self.party.pull()?).ok_or_else(malformed)?;
+        if evaluated.len() != inputs.len() {
+            return Err(malformed());
+        }
+        if inputs.is_empty() {
+            return Ok(vec![]);
+        }
+        let proof = cp92::Proof::from_bytes(&self.party.pull()?).map_err(|_| malformed())?;
+        let mut transcript = Transcript::new(b"oprf");
+        let (m, z) = combine(&mut transcript, &self.public, &blinded, &evaluated);
+        let bases = [ProjectivePoint::GENERATOR, m];
+        if !cp92::verify(&mut transcript, &bases, &[self.public, z], &proof) {
+            return Err(Error::MPCError(MPCErrorKind::InvalidShare));
+        }
+        Ok(inputs
+            .iter()
+            .zip(evaluated.iter().zip(&blinds))
+            .map(|(input, (point, r))| finalize(input, &(*point * r.invert().unwrap())))
+            .collect())
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::store::{disk::DiskStore, Store};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers = [
+        SocketAddr::from(([127, 0, 0, 1], 9180)),
+        SocketAddr::from(([127, 0, 0, 1], 9181)),
+    ];
+    let mut store = DiskStore::new("data/Alice", "oprf").unwrap();
+    store.set("key", &Key::random()).unwrap();
+    let key = store.get::<Key>("key").unwrap();
+    let public = key.public();
+    let inputs: [&[u8]; 3] = [b"alice@example.com", b"hunter2", b""];
+    let (mut outputs, mut forged) = (vec![], None);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let server = Server::new(TwoParty::new(0, &peers).unwrap(), key.clone());
+            server.evaluate().unwrap();
+            server.evaluate().unwrap();
+            server.evaluate().unwrap();
+        });
+        scope.spawn(|| {
+            let party = TwoParty::new(1, &peers).unwrap();
+            let client = Client::new(party, public);
+            outputs = client.evaluate(&inputs).unwrap();
+            assert!(client.evaluate(&[]).unwrap().is_empty());
+            // a server key other than the published one is detected
+            let client = Client::new(client.party, public + ProjectivePoint::GENERATOR);
+            forged = Some(client.evaluate(&inputs[..1]));
+        });
+    });
+    let expected: Vec<Output> = inputs.iter().map(|input| key.evaluate(input)).collect();
+    assert_eq!(expected, outputs);
+    assert_ne!(outputs[0], Key::random().evaluate(inputs[0]));
+    assert_eq!(
+        Err(Error::MPCError(MPCErrorKind::InvalidShare)),
+        forged.unwrap()
+    );
+}
@@ -2,7 +2,10 @@
 
 use std::ops::Deref;
 
-use super::error::Result;
+use super::error::{Error, MPCErrorKind, Result};
+use super::party::Channel;
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use blake2::{Blake2b512, Digest};
 
 /// choice in OT with range guaranteed to be in [0, N)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -36,6 +39,123 @@ pub trait OTReceive<const N: usize, const L: usize> {
     fn receive(&self, choice: &Choice<N>) -> Result<[u8; L]>;
 }
 
+/// batch of random 1 out of 2 oblivious transfers of length L, where the
+/// sender obtains two random messages and the receiver the chosen one
+pub trait RandomOTSend<const L: usize> {
+    fn send_random(&self, count: usize) -> Result<Vec<[[u8; L]; 2]>>;
+}
+
+/// batch of random 1 out of 2 oblivious transfers of length L, receive
+pub trait RandomOTReceive<const L: usize> {
+    fn receive_random(&self, choices: &[bool]) -> Result<Vec<[u8; L]>>;
+}
+
+/// batch of correlated 1 out of 2 oblivious transfers of length L, where the
+/// sender obtains random messages `m0` whose pairs are `m0 ^ delta`
+pub trait CorrelatedOTSend<const L: usize> {
+    fn send_correlated(&self, delta: &[u8; L], count: usize) -> Result<Vec<[u8; L]>>;
+}
+
+/// batch of correlated 1 out of 2 oblivious transfers of length L, receive
+pub trait CorrelatedOTReceive<const L: usize> {
+    fn receive_correlated(&self, choices: &[bool]) -> Result<Vec<[u8; L]>>;
+}
+
+fn xor<const L: usize>(a: &[u8; L], b: &[u8; L]) -> [u8; L] {
+    std::array::from_fn(|i| a[i] ^ b[i])
+}
+
+pub(crate) fn malformed() -> Error {
+    Error::MPCError(MPCErrorKind::MalformedMessage)
+}
+
+pub(crate) fn random_seed() -> u128 {
+    (OsRng.next_u64() as u128) << 64 | OsRng.next_u64() as u128
+}
+
+/// expands a seed into `len` bytes
+pub(crate) fn prg(seed: &[u8; 16], len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    for counter in 0..len.div_ceil(64) as u64 {
+        let hash = Blake2b512::new()
+            .chain_update(seed)
+            .chain_update(counter.to_le_bytes())
+            .finalize();
+        bytes.extend(hash);
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+pub(crate) fn bit(bytes: &[u8], i: usize) -> bool {
+    bytes[i / 8] >> (i % 8) & 1 == 1
+}
+
+/// transposes the `8 * B` columns of `count` bits of an OT extension into
+/// `count` rows of `B` bytes
+pub(crate) fn transpose<const B: usize>(columns: &[Vec<u8>], count: usize) -> Vec<[u8; B]> {
+    assert_eq!(columns.len(), 8 * B);
+    let mut rows = vec![[0u8; B]; count];
+    for (i, column) in columns.iter().enumerate() {
+        for (j, row) in rows.iter_mut().enumerate() {
+            row[i / 8] |= (bit(column, j) as u8) << (i % 8);
+        }
+    }
+    rows
+}
+
+/// sends chosen messages over random OTs computed in advance.
+/// The receiver reveals which random choices differ from its real ones, and
+/// the sender masks each pair of messages with the accordingly swapped pair.
+pub fn send_chosen<C: Channel, const L: usize>(
+    channel: &C,
+    randoms: &[[[u8; L]; 2]],
+    messages: &[[[u8; L]; 2]],
+) -> Result<()> {
+    assert_eq!(randoms.len(), messages.len());
+    let flips = channel.pull()?;
+    if flips.len() != messages.len() || flips.iter().any(|&flip| flip > 1) {
+        return Err(malformed());
+    }
+    let mut ciphers = Vec::with_capacity(2 * L * messages.len());
+    for ((random, message), flip) in randoms.iter().zip(messages).zip(flips) {
+        let flip = flip as usize;
+        ciphers.extend(xor(&message[0], &random[flip]));
+        ciphers.extend(xor(&message[1], &random[1 ^ flip]));
+    }
+    channel.push(&ciphers)
+}
+
+/// receives chosen messages over random OTs computed in advance, given the
+/// random choices and the messages received for them
+pub fn receive_chosen<C: Channel, const L: usize>(
+    channel: &C,
+    randoms: &[(bool, [u8; L])],
+    choices: &[bool],
+) -> Result<Vec<[u8; L]>> {
+    assert_eq!(randoms.len(), choices.len());
+    let flips: Vec<u8> = randoms
+        .iter()
+        .zip(choices)
+        .map(|((random, _), choice)| (random ^ choice) as u8)
+        .collect();
+    channel.push(&flips)?;
+    let ciphers = channel.pull()?;
+    if ciphers.len() != 2 * L * choices.len() {
+        return Err(malformed());
+    }
+    Ok(ciphers
+        .chunks_exact(2 * L)
+        .zip(randoms.iter().zip(choices))
+        .map(|(pair, ((_, random), &choice))| {
+            let cipher = &pair[choice as usize * L..][..L];
+            xor(cipher.try_into().unwrap(), random)
+        })
+        .collect())
+}
+
+pub mod bcgikrs22;
 pub mod co15;
 pub mod functionality;
+pub mod iknp03;
 pub mod np01;
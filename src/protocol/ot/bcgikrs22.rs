@@ -0,0 +1,414 @@
+//! implements silent VOLE and silent correlated OT over GF(2^128) from expand-accumulate codes
+//! Correlated Pseudorandomness from Expand-Accumulate Codes
+//! https://eprint.iacr.org/2022/1014
+//! The receiver picks a regular sparse noise vector `e`, one nonzero entry per
+//! block. For each block, the sender expands a GGM tree whose leaves are `v`,
+//! and the receiver learns all leaves except the one at its noise position
+//! through one OT per level, so that both hold `w = v + e * delta` after one
+//! correction. Both parties then compress their long vectors with the same
+//! public linear map, the transpose of an expand-accumulate code, which turns
+//! the sparse `e` into a pseudorandom `u` under the LPN assumption.
+//! The communication is logarithmic in the number of outputs per block, with
+//! base OTs from the IKNP extension. Security holds against semi-honest parties.
+
+use super::{bit, iknp03, malformed, random_seed, RandomOTReceive, RandomOTSend};
+use crate::math::galois::{binaryfield::BinaryField, FiniteField};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::protocol::error::Result;
+use crate::protocol::party::{Channel, TwoParty};
+use blake2::{Blake2b512, Digest};
+
+/// element of GF(2^128), the field of the correlations
+pub type Block = BinaryField<128, 2>;
+
+/// number of noisy positions, i.e., blocks of the noise vector, by default
+const NOISE: usize = 128;
+/// positions of the accumulated noise summed into each output
+const WEIGHT: usize = 8;
+
+/// sizes of a correlation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Params {
+    pub outputs: usize,
+    /// number of blocks, each holding one noisy position
+    pub blocks: usize,
+    /// depth of the GGM tree of a block
+    pub depth: u32,
+}
+
+impl Params {
+    /// chooses blocks so that the noise vector is about twice as long as the outputs
+    pub fn new(outputs: usize) -> Self {
+        let block = (2 * outputs).div_ceil(NOISE).next_power_of_two().max(2);
+        Self {
+            outputs,
+            blocks: NOISE,
+            depth: block.trailing_zeros(),
+        }
+    }
+
+    /// length of the noise vector
+    pub fn len(&self) -> usize {
+        self.blocks << self.depth
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// produces `(delta, v)` of a VOLE or a correlated OT
+pub struct Sender<C = TwoParty>(pub C);
+/// produces `(u, w)` with `w = v + u * delta`
+pub struct Receiver<C = TwoParty>(pub C);
+
+fn block(seed: u128) -> Block {
+    Block::ZERO.decode(&seed.to_le_bytes()).unwrap()
+}
+
+/// expands a node of the GGM tree into its two children
+fn children(seed: u128) -> [u128; 2] {
+    let hash = Blake2b512::new()
+        .chain_update(seed.to_le_bytes())
+        .finalize();
+    std::array::from_fn(|i| u128::from_le_bytes(hash[16 * i..16 * (i + 1)].try_into().unwrap()))
+}
+
+/// expands a GGM tree, and returns the leaves and the sums of the left and the
+/// right children at each level
+fn expand(root: u128, depth: u32) -> (Vec<u128>, Vec<[u128; 2]>) {
+    let mut nodes = vec![root];
+    let mut sums = Vec::with_capacity(depth as usize);
+    for _ in 0..depth {
+        nodes = nodes.iter().flat_map(|&node| children(node)).collect();
+        let mut sum = [0u128; 2];
+        for (i, node) in nodes.iter().enumerate() {
+            sum[i % 2] ^= node;
+        }
+        sums.push(sum);
+    }
+    (nodes, sums)
+}
+
+/// recovers all leaves except the one at `point` from the sums of the siblings
+/// of its path, leaving that leaf zero
+fn puncture(point: usize, depth: u32, sums: &[u128]) -> Vec<u128> {
+    let mut nodes: Vec<Option<u128>> = vec![None];
+    for (level, sum) in sums.iter().enumerate() {
+        let bit = point >> (depth as usize - 1 - level) & 1;
+        let mut next: Vec<Option<u128>> = nodes
+            .iter()
+            .flat_map(|node| match node {
+                Some(node) => children(*node).map(Some),
+                None => [None, None],
+            })
+            .collect();
+        let path = point >> (depth as usize - level);
+        let known = next
+            .iter()
+            .skip(1 - bit)
+            .step_by(2)
+            .flatten()
+            .fold(*sum, |acc, node| acc ^ node);
+        next[2 * path + 1 - bit] = Some(known);
+        nodes = next;
+    }
+    nodes.into_iter().map(|node| node.unwrap_or(0)).collect()
+}
+
+/// positions of the accumulated noise summed into the output `i`
+fn positions(seed: &[u8; 16], i: usize, len: usize) -> [usize; WEIGHT] {
+    let hash = Blake2b512::new()
+        .chain_update(seed)
+        .chain_update((i as u64).to_le_bytes())
+        .finalize();
+    std::array::from_fn(|j| {
+        (u64::from_le_bytes(hash[8 * j..8 * (j + 1)].try_into().unwrap()) % len as u64) as usize
+    })
+}
+
+/// accumulates a vector into its prefix sums, and sums `WEIGHT` random
+/// positions of them into each output
+fn compress(seed: &[u8; 16], input: &[Block], outputs: usize) -> Vec<Block> {
+    let mut sum = Block::ZERO;
+    let accumulated: Vec<Block> = input
+        .iter()
+        .map(|x| {
+            sum += x;
+            sum
+        })
+        .collect();
+    (0..outputs)
+        .map(|i| {
+            positions(seed, i, input.len())
+                .iter()
+                .fold(Block::ZERO, |acc, &j| acc + accumulated[j])
+        })
+        .collect()
+}
+
+/// hashes a correlation of the `i`-th OT into a message
+fn hash<const L: usize>(i: usize, value: &Block) -> [u8; L] {
+    assert!(L <= 64, "L must be less than or equal to 64 to be safe");
+    let hash = Blake2b512::new()
+        .chain_update((i as u64).to_le_bytes())
+        .chain_update(value.encode())
+        .finalize();
+    hash[..L].try_into().unwrap()
+}
+
+fn words(bytes: &[u8], count: usize) -> Result<Vec<u128>> {
+    if bytes.len() != 16 * count {
+        return Err(malformed());
+    }
+    Ok(bytes
+        .chunks_exact(16)
+        .map(|chunk| u128::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+impl<C: Channel> Sender<C> {
+    /// runs the protocol where the noise values are random field elements if
+    /// `vole`, and one otherwise
+    fn run(&self, params: &Params, vole: bool) -> Result<(Block, Vec<Block>)> {
+        let mut seed = [0u8; 16];
+        OsRng.fill_bytes(&mut seed);
+        self.0.push(&seed)?;
+        let delta = block(random_seed());
+        let depth = params.depth as usize;
+        let count = params.blocks * (depth + if vole { 128 } else { 0 });
+        let randoms: Vec<[[u8; 16]; 2]> = iknp03::Sender(&self.0).send_random(count)?;
+        let randoms: Vec<[u128; 2]> = randoms
+            .iter()
+            .map(|pair| pair.map(u128::from_le_bytes))
+            .collect();
+        let (mut ggm, randoms) = randoms.split_at(params.blocks * depth);
+
+        // one OT per level reveals the sum of the siblings off the noisy path
+        let mut leaves = Vec::with_capacity(params.len());
+        let mut masked = Vec::with_capacity(32 * params.blocks * depth);
+        for _ in 0..params.blocks {
+            let (nodes, sums) = expand(random_seed(), params.depth);
+            let (pads, rest) = ggm.split_at(depth);
+            ggm = rest;
+            for (sum, pad) in sums.iter().zip(pads) {
+                masked.extend((sum[0] ^ pad[0]).to_le_bytes());
+                masked.extend((sum[1] ^ pad[1]).to_le_bytes());
+            }
+            leaves.extend(nodes.into_iter().map(block));
+        }
+        self.0.push(&masked)?;
+
+        // the noise values are one, or shared with a product of bits and powers of delta
+        let offsets: Vec<Block> = if vole {
+            let x = Block::ZERO.embed(2);
+            let mut powers = Vec::with_capacity(128);
+            powers.push(delta);
+            for i in 1..128 {
+                powers.push(powers[i - 1] * x);
+            }
+            let mut corrections = Vec::with_capacity(16 * randoms.len());
+            let offsets = randoms
+                .chunks_exact(128)
+                .map(|pairs| {
+                    let mut offset = Block::ZERO;
+                    for (pair, power) in pairs.iter().zip(&powers) {
+                        let correction = block(pair[0] ^ pair[1]) + power;
+                        corrections.extend(correction.encode());
+                        offset += block(pair[0]);
+                    }
+                    offset
+                })
+                .collect();
+            self.0.push(&corrections)?;
+            offsets
+        } else {
+            vec![delta; params.blocks]
+        };
+        let mut sums = Vec::with_capacity(16 * params.blocks);
+        for (leaves, offset) in leaves.chunks_exact(1 << depth).zip(offsets) {
+            let sum = leaves.iter().fold(offset, |acc, leaf| acc + leaf);
+            sums.extend(sum.encode());
+        }
+        self.0.push(&sums)?;
+        Ok((delta, compress(&seed, &leaves, params.outputs)))
+    }
+
+    /// produces `delta` and `v` of a VOLE of `params.outputs` entries
+    pub fn vole(&self, params: &Params) -> Result<(Block, Vec<Block>)> {
+        self.run(params, true)
+    }
+
+    /// produces `delta` and `v` of correlated OTs, where the receiver's
+    /// choice bits are the entries of `u`
+    pub fn correlated(&self, params: &Params) -> Result<(Block, Vec<Block>)> {
+        self.run(params, false)
+    }
+}
+
+impl<C: Channel> Receiver<C> {
+    /// runs the protocol, and returns the noise vector and `w`
+    fn run(&self, params: &Params, vole: bool) -> Result<(Vec<Block>, Vec<Block>)> {
+        let seed: [u8; 16] = self.0.pull()?.try_into().map_err(|_| malformed())?;
+        let depth = params.depth as usize;
+        let points: Vec<usize> = (0..params.blocks)
+            .map(|_| OsRng.next_u64() as usize % (1 << depth))
+            .collect();
+        let values: Vec<Block> = (0..params.blocks)
+            .map(|_| {
+                if vole {
+                    block(random_seed())
+                } else {
+                    Block::ONE
+                }
+            })
+            .collect();
+        let mut choices: Vec<bool> = points
+            .iter()
+            .flat_map(|point| (0..depth).map(move |level| point >> (depth - 1 - level) & 1 == 0))
+            .collect();
+        if vole {
+            for value in &values {
+                let bytes = value.encode();
+                choices.extend((0..128).map(|i| bit(&bytes, i)));
+            }
+        }
+        let randoms: Vec<[u8; 16]> = iknp03::Receiver(&self.0).receive_random(&choices)?;
+        let randoms: Vec<u128> = randoms.into_iter().map(u128::from_le_bytes).collect();
+
+        let masked = words(&self.0.pull()?, 2 * params.blocks * depth)?;
+        let mut leaves = Vec::with_capacity(params.len());
+        for (k, point) in points.iter().enumerate() {
+            let sums: Vec<u128> = (k * depth..(k + 1) * depth)
+                .map(|i| masked[2 * i + choices[i] as usize] ^ randoms[i])
+                .collect();
+            leaves.extend(puncture(*point, params.depth, &sums).into_iter().map(block));
+        }
+
+        let offsets: Vec<Block> = if vole {
+            let corrections = words(&self.0.pull()?, 128 * params.blocks)?;
+            let start = params.blocks * depth;
+            (0..params.blocks)
+                .map(|k| {
+                    (start + 128 * k..start + 128 * (k + 1)).fold(Block::ZERO, |acc, i| {
+                        let share = if choices[i] {
+                            randoms[i] ^ corrections[i - start]
+                        } else {
+                            randoms[i]
+                        };
+                        acc + block(share)
+                    })
+                })
+                .collect()
+        } else {
+            vec![Block::ZERO; params.blocks]
+        };
+        let sums = words(&self.0.pull()?, params.blocks)?;
+        let mut noise = vec![Block::ZERO; params.len()];
+        for k in 0..params.blocks {
+            let block_leaves = &mut leaves[k << depth..(k + 1) << depth];
+            let known = block_leaves
+                .iter()
+                .fold(block(sums[k]) + offsets[k], |acc, leaf| acc + leaf);
+            block_leaves[points[k]] = known;
+            noise[(k << depth) + points[k]] = values[k];
+        }
+        Ok((
+            compress(&seed, &noise, params.outputs),
+            compress(&seed, &leaves, params.outputs),
+        ))
+    }
+
+    /// produces `u` and `w` of a VOLE of `params.outputs` entries
+    pub fn vole(&self, params: &Params) -> Result<(Vec<Block>, Vec<Block>)> {
+        self.run(params, true)
+    }
+
+    /// produces the choice bits and `w` of correlated OTs
+    pub fn correlated(&self, params: &Params) -> Result<(Vec<bool>, Vec<Block>)> {
+        let (u, w) = self.run(params, false)?;
+        Ok((u.into_iter().map(|bit| bit == Block::ONE).collect(), w))
+    }
+}
+
+/// derandomizes the choices of silent OTs with one bit per OT
+impl<C: Channel, const L: usize> RandomOTSend<L> for Sender<C> {
+    fn send_random(&self, count: usize) -> Result<Vec<[[u8; L]; 2]>> {
+        let (delta, v) = self.correlated(&Params::new(count))?;
+        let flips = self.0.pull()?;
+        if flips.len() != count.div_ceil(8) {
+            return Err(malformed());
+        }
+        Ok(v.iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let mut pair = [hash(i, v), hash(i, &(*v + delta))];
+                if bit(&flips, i) {
+                    pair.swap(0, 1);
+                }
+                pair
+            })
+            .collect())
+    }
+}
+
+impl<C: Channel, const L: usize> RandomOTReceive<L> for Receiver<C> {
+    fn receive_random(&self, choices: &[bool]) -> Result<Vec<[u8; L]>> {
+        let (bits, w) = self.correlated(&Params::new(choices.len()))?;
+        let mut flips = vec![0u8; choices.len().div_ceil(8)];
+        for (i, (bit, choice)) in bits.iter().zip(choices).enumerate() {
+            flips[i / 8] |= ((bit ^ choice) as u8) << (i % 8);
+        }
+        self.0.push(&flips)?;
+        Ok(w.iter().enumerate().map(|(i, w)| hash(i, w)).collect())
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers = [
+        SocketAddr::from(([127, 0, 0, 1], 9170)),
+        SocketAddr::from(([127, 0, 0, 1], 9171)),
+    ];
+    let params = Params::new(1000);
+    assert_eq!(2048, params.len());
+    let choices: Vec<bool> = (0..100).map(|i| i % 3 == 0).collect();
+    let (mut vole, mut correlated, mut randoms) = (None, None, vec![]);
+    let (mut u, mut bits, mut received) = (None, None, vec![]);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let sender = Sender(TwoParty::new(0, &peers).unwrap());
+            vole = Some(sender.vole(&params).unwrap());
+            correlated = Some(sender.correlated(&params).unwrap());
+            randoms = sender.send_random(choices.len()).unwrap();
+        });
+        scope.spawn(|| {
+            let receiver = Receiver(TwoParty::new(1, &peers).unwrap());
+            u = Some(receiver.vole(&params).unwrap());
+            bits = Some(receiver.correlated(&params).unwrap());
+            received = receiver.receive_random(&choices).unwrap();
+        });
+    });
+    let ((delta, v), (u, w)) = (vole.unwrap(), u.unwrap());
+    assert_eq!(params.outputs, w.len());
+    for i in 0..params.outputs {
+        assert_eq!(w[i], v[i] + u[i] * delta);
+    }
+    assert!(u.iter().skip(1).all(|x| *x != u[0]));
+
+    let ((delta, v), (bits, w)) = (correlated.unwrap(), bits.unwrap());
+    for i in 0..params.outputs {
+        assert_eq!(w[i], if bits[i] { v[i] + delta } else { v[i] });
+    }
+    let ones = bits.iter().filter(|&&bit| bit).count();
+    assert!(ones > 400 && ones < 600);
+
+    let randoms: Vec<[[u8; 16]; 2]> = randoms;
+    for (i, choice) in choices.iter().enumerate() {
+        assert_eq!(received[i], randoms[i][*choice as usize]);
+        assert_ne!(received[i], randoms[i][1 - *choice as usize]);
+    }
+}
@@ -2,24 +2,26 @@
 //! The Simplest Protocol for Oblivious Transfer
 //! https://eprint.iacr.org/2015/267.pdf
 
-use super::{Choice, OTReceive, OTSend};
+use super::{
+    Choice, CorrelatedOTReceive, CorrelatedOTSend, OTReceive, OTSend, RandomOTReceive, RandomOTSend,
+};
 use crate::protocol::error::Result;
-use crate::protocol::party::TwoParty;
+use crate::protocol::party::{Channel, TwoParty};
 use blake2::{Blake2b512, Digest};
 use k256::{
     elliptic_curve::{
         group::GroupEncoding,
         ops::{LinearCombination, MulByGenerator},
-        rand_core::OsRng,
+        rand_core::{OsRng, RngCore},
         Field,
     },
     ProjectivePoint, Scalar,
 };
 
-pub struct Sender(TwoParty);
-pub struct Receiver(TwoParty);
+pub struct Sender<C = TwoParty>(pub C);
+pub struct Receiver<C = TwoParty>(pub C);
 
-impl<const N: usize, const L: usize> OTSend<N, L> for Sender {
+impl<C: Channel, const N: usize, const L: usize> OTSend<N, L> for Sender<C> {
     fn send(&self, messages: &[[u8; L]; N]) -> Result<()> {
         // todo: use precomputation to speed up
         // key exchange
@@ -52,7 +54,7 @@ impl<const N: usize, const L: usize> OTSend<N, L> for Sender {
     }
 }
 
-impl<const N: usize, const L: usize> OTReceive<N, L> for Receiver {
+impl<C: Channel, const N: usize, const L: usize> OTReceive<N, L> for Receiver<C> {
     fn receive(&self, choice: &Choice<N>) -> Result<[u8; L]> {
         // key exchange
         let bytes: [u8; 33] = self.0.pull()?.try_into().unwrap();
@@ -87,6 +89,47 @@ impl<const N: usize, const L: usize> OTReceive<N, L> for Receiver {
     }
 }
 
+/// runs one base OT per random pair
+impl<C: Channel, const L: usize> RandomOTSend<L> for Sender<C> {
+    fn send_random(&self, count: usize) -> Result<Vec<[[u8; L]; 2]>> {
+        (0..count)
+            .map(|_| {
+                let mut messages = [[0u8; L]; 2];
+                messages.iter_mut().for_each(|m| OsRng.fill_bytes(m));
+                OTSend::<2, L>::send(self, &messages).map(|()| messages)
+            })
+            .collect()
+    }
+}
+
+impl<C: Channel, const L: usize> RandomOTReceive<L> for Receiver<C> {
+    fn receive_random(&self, choices: &[bool]) -> Result<Vec<[u8; L]>> {
+        choices
+            .iter()
+            .map(|&choice| OTReceive::<2, L>::receive(self, &Choice(choice as usize)))
+            .collect()
+    }
+}
+
+impl<C: Channel, const L: usize> CorrelatedOTSend<L> for Sender<C> {
+    fn send_correlated(&self, delta: &[u8; L], count: usize) -> Result<Vec<[u8; L]>> {
+        (0..count)
+            .map(|_| {
+                let mut m0 = [0u8; L];
+                OsRng.fill_bytes(&mut m0);
+                let m1 = std::array::from_fn(|i| m0[i] ^ delta[i]);
+                OTSend::<2, L>::send(self, &[m0, m1]).map(|()| m0)
+            })
+            .collect()
+    }
+}
+
+impl<C: Channel, const L: usize> CorrelatedOTReceive<L> for Receiver<C> {
+    fn receive_correlated(&self, choices: &[bool]) -> Result<Vec<[u8; L]>> {
+        self.receive_random(choices)
+    }
+}
+
 #[test]
 fn test_correctness() {
     use crate::protocol::party::TwoParty;
@@ -102,15 +145,32 @@ fn test_correctness() {
     let index = 2;
     let choice = Choice::<4>::new(index).unwrap();
     let mut result = [4u8; 4];
+    let choices = [true, false];
+    let delta = [7u8; 4];
+    let (mut randoms, mut correlated): (Vec<[[u8; 4]; 2]>, _) = (vec![], vec![]);
+    let (mut received, mut received_correlated) = (vec![], vec![]);
     thread::scope(|scope| {
         scope.spawn(|| {
             let sender = Sender(TwoParty::new(0, &peers).unwrap());
             sender.send(msgs).unwrap();
+            randoms = sender.send_random(2).unwrap();
+            correlated = sender.send_correlated(&delta, 2).unwrap();
         });
         scope.spawn(|| {
             let receiver = Receiver(TwoParty::new(1, &peers).unwrap());
             result = receiver.receive(&choice).unwrap();
+            received = receiver.receive_random(&choices).unwrap();
+            received_correlated = receiver.receive_correlated(&choices).unwrap();
         });
     });
     assert_eq!(result, msgs[index]);
+    for i in 0..2 {
+        assert_eq!(received[i], randoms[i][choices[i] as usize]);
+        let expected: [u8; 4] = if choices[i] {
+            std::array::from_fn(|j| correlated[i][j] ^ delta[j])
+        } else {
+            correlated[i]
+        };
+        assert_eq!(received_correlated[i], expected);
+    }
 }
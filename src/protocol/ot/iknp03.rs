@@ -0,0 +1,194 @@
+//! implements the OT extension of Ishai, Kilian, Nissim and Petrank
+//! Extending Oblivious Transfers Efficiently
+//! https://www.iacr.org/archive/crypto2003/27290145/27290145.pdf
+//! 128 base OTs of seeds, in which the roles are swapped, give the sender a
+//! secret `s` and rows `q_j = t_j ^ r_j s`, where `t_j` is known to the
+//! receiver with choice `r_j`. Hashing `q_j` and `q_j ^ s` yields the random
+//! messages, and the receiver can only hash the one of its choice. Each batch
+//! costs the base OTs once and then one bit per OT and base OT.
+
+use super::{co15, Choice, CorrelatedOTReceive, CorrelatedOTSend, OTReceive, OTSend};
+use super::{malformed, prg, random_seed, transpose, RandomOTReceive, RandomOTSend};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::protocol::error::Result;
+use crate::protocol::party::{Channel, TwoParty};
+use blake2::{Blake2b512, Digest};
+
+/// number of base OTs, which is the computational security parameter
+const BASE: usize = 128;
+
+pub struct Sender<C = TwoParty>(pub C);
+pub struct Receiver<C = TwoParty>(pub C);
+
+/// hashes the row of the `j`-th OT into a message
+fn hash<const L: usize>(j: usize, row: u128) -> [u8; L] {
+    assert!(L <= 64, "L must be less than or equal to 64 to be safe");
+    let hash = Blake2b512::new()
+        .chain_update((j as u64).to_le_bytes())
+        .chain_update(row.to_le_bytes())
+        .finalize();
+    hash[..L].try_into().unwrap()
+}
+
+/// transposes BASE columns of `count` bits into rows of BASE bits
+fn rows(columns: &[Vec<u8>], count: usize) -> Vec<u128> {
+    transpose::<{ BASE / 8 }>(columns, count)
+        .into_iter()
+        .map(u128::from_le_bytes)
+        .collect()
+}
+
+impl<C: Channel> Sender<C> {
+    /// extends the base OTs to `count` rows, and returns the secret and the rows
+    fn extend(&self, count: usize) -> Result<(u128, Vec<u128>)> {
+        let len = count.div_ceil(8);
+        let secret = random_seed();
+        let receiver = co15::Receiver(&self.0);
+        let mut columns = Vec::with_capacity(BASE);
+        for i in 0..BASE {
+            let choice = Choice::<2>::new((secret >> i & 1) as usize).unwrap();
+            let seed: [u8; 16] = receiver.receive(&choice)?;
+            columns.push(prg(&seed, len));
+        }
+        let masks = self.0.pull()?;
+        if masks.len() != BASE * len {
+            return Err(malformed());
+        }
+        for (i, (column, mask)) in columns.iter_mut().zip(masks.chunks_exact(len)).enumerate() {
+            if secret >> i & 1 == 1 {
+                column.iter_mut().zip(mask).for_each(|(c, m)| *c ^= m);
+            }
+        }
+        Ok((secret, rows(&columns, count)))
+    }
+}
+
+impl<C: Channel> Receiver<C> {
+    /// extends the base OTs to one row per choice
+    fn extend(&self, choices: &[bool]) -> Result<Vec<u128>> {
+        let len = choices.len().div_ceil(8);
+        let mut packed = vec![0u8; len];
+        for (j, &choice) in choices.iter().enumerate() {
+            packed[j / 8] |= (choice as u8) << (j % 8);
+        }
+        let sender = co15::Sender(&self.0);
+        let mut columns = Vec::with_capacity(BASE);
+        let mut masks = Vec::with_capacity(BASE * len);
+        for _ in 0..BASE {
+            let mut seeds = [[0u8; 16]; 2];
+            seeds.iter_mut().for_each(|seed| OsRng.fill_bytes(seed));
+            sender.send(&seeds)?;
+            let column = prg(&seeds[0], len);
+            let mask = prg(&seeds[1], len);
+            masks.extend((0..len).map(|b| column[b] ^ mask[b] ^ packed[b]));
+            columns.push(column);
+        }
+        self.0.push(&masks)?;
+        Ok(rows(&columns, choices.len()))
+    }
+}
+
+impl<C: Channel, const L: usize> RandomOTSend<L> for Sender<C> {
+    fn send_random(&self, count: usize) -> Result<Vec<[[u8; L]; 2]>> {
+        let (secret, rows) = self.extend(count)?;
+        Ok(rows
+            .iter()
+            .enumerate()
+            .map(|(j, &row)| [hash(j, row), hash(j, row ^ secret)])
+            .collect())
+    }
+}
+
+impl<C: Channel, const L: usize> RandomOTReceive<L> for Receiver<C> {
+    fn receive_random(&self, choices: &[bool]) -> Result<Vec<[u8; L]>> {
+        let rows = self.extend(choices)?;
+        Ok(rows
+            .iter()
+            .enumerate()
+            .map(|(j, &row)| hash(j, row))
+            .collect())
+    }
+}
+
+/// sends the difference of the second random message from `m0 ^ delta`
+impl<C: Channel, const L: usize> CorrelatedOTSend<L> for Sender<C> {
+    fn send_correlated(&self, delta: &[u8; L], count: usize) -> Result<Vec<[u8; L]>> {
+        let randoms: Vec<[[u8; L]; 2]> = self.send_random(count)?;
+        let mut corrections = Vec::with_capacity(L * count);
+        for [m0, m1] in &randoms {
+            corrections.extend((0..L).map(|i| m0[i] ^ m1[i] ^ delta[i]));
+        }
+        self.0.push(&corrections)?;
+        Ok(randoms.into_iter().map(|[m0, _]| m0).collect())
+    }
+}
+
+impl<C: Channel, const L: usize> CorrelatedOTReceive<L> for Receiver<C> {
+    fn receive_correlated(&self, choices: &[bool]) -> Result<Vec<[u8; L]>> {
+        let randoms: Vec<[u8; L]> = self.receive_random(choices)?;
+        let corrections = self.0.pull()?;
+        if corrections.len() != L * choices.len() {
+            return Err(malformed());
+        }
+        Ok(randoms
+            .into_iter()
+            .zip(corrections.chunks_exact(L))
+            .zip(choices)
+            .map(|((m, correction), &choice)| {
+                if choice {
+                    std::array::from_fn(|i| m[i] ^ correction[i])
+                } else {
+                    m
+                }
+            })
+            .collect())
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::{receive_chosen, send_chosen};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers = [
+        SocketAddr::from(([127, 0, 0, 1], 9160)),
+        SocketAddr::from(([127, 0, 0, 1], 9161)),
+    ];
+    let count = 300;
+    let choices: Vec<bool> = (0..count).map(|j| j % 3 == 0).collect();
+    let wanted: Vec<bool> = (0..count).map(|j| j % 2 == 0).collect();
+    let messages: Vec<[[u8; 8]; 2]> = (0..count as u64)
+        .map(|j| [j.to_le_bytes(), (j << 32).to_le_bytes()])
+        .collect();
+    let delta = [0x5au8; 16];
+    let (mut randoms, mut correlated) = (vec![], vec![]);
+    let (mut received, mut received_correlated, mut chosen) = (vec![], vec![], vec![]);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let sender = Sender(TwoParty::new(0, &peers).unwrap());
+            randoms = sender.send_random(count).unwrap();
+            correlated = sender.send_correlated(&delta, count).unwrap();
+            send_chosen(&sender.0, &randoms, &messages).unwrap();
+        });
+        scope.spawn(|| {
+            let receiver = Receiver(TwoParty::new(1, &peers).unwrap());
+            received = receiver.receive_random(&choices).unwrap();
+            received_correlated = receiver.receive_correlated(&choices).unwrap();
+            let randoms: Vec<(bool, [u8; 8])> =
+                choices.iter().copied().zip(received.clone()).collect();
+            chosen = receive_chosen(&receiver.0, &randoms, &wanted).unwrap();
+        });
+    });
+    for j in 0..count {
+        let choice = choices[j] as usize;
+        assert_eq!(received[j], randoms[j][choice]);
+        assert_ne!(received[j], randoms[j][1 - choice]);
+        let expected: [u8; 16] = std::array::from_fn(|i| correlated[j][i] ^ delta[i]);
+        assert_eq!(
+            received_correlated[j],
+            if choices[j] { expected } else { correlated[j] }
+        );
+        assert_eq!(chosen[j], messages[j][wanted[j] as usize]);
+    }
+}
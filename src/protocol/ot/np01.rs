@@ -12,8 +12,8 @@ use k256::{
     ProjectivePoint, PublicKey,
 };
 
-pub struct Sender(TwoParty);
-pub struct Receiver(TwoParty);
+pub struct Sender(pub TwoParty);
+pub struct Receiver(pub TwoParty);
 
 impl<const N: usize, const L: usize> OTSend<N, L> for Sender {
     fn send(&self, messages: &[[u8; L]; N]) -> Result<()> {
@@ -1,6 +1,6 @@
 //! provides party utilities
 
-use super::error::Result;
+use super::error::{Error, MPCErrorKind, Result};
 use std::cell::RefCell;
 use std::io::{Read, Write};
 use std::net::{SocketAddr, TcpListener, TcpStream};
@@ -8,6 +8,9 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+/// largest message in bytes that a party accepts from a peer
+pub const MAX_MESSAGE: usize = 1 << 28;
+
 /// Abstract party trait
 pub struct MultiParty<const N: usize> {
     /// party id (assume server or sender id is 0)
@@ -49,6 +52,11 @@ impl<const N: usize> MultiParty<N> {
     pub fn download(&self) -> Result<Vec<u8>> {
         self.session.borrow_mut().recv(0)
     }
+
+    /// view the connection to party `id` as a channel
+    pub fn peer(&self, id: usize) -> Peer<'_, N> {
+        Peer { party: self, id }
+    }
 }
 
 pub type TwoParty = MultiParty<2>;
@@ -65,6 +73,50 @@ impl TwoParty {
     }
 }
 
+/// point-to-point channel to another party, over which two-party protocols run
+pub trait Channel {
+    /// send message to the other party
+    fn push(&self, msg: &[u8]) -> Result<()>;
+    /// receive message from the other party
+    fn pull(&self) -> Result<Vec<u8>>;
+}
+
+impl Channel for TwoParty {
+    fn push(&self, msg: &[u8]) -> Result<()> {
+        TwoParty::push(self, msg)
+    }
+
+    fn pull(&self) -> Result<Vec<u8>> {
+        TwoParty::pull(self)
+    }
+}
+
+impl<C: Channel + ?Sized> Channel for &C {
+    fn push(&self, msg: &[u8]) -> Result<()> {
+        (**self).push(msg)
+    }
+
+    fn pull(&self) -> Result<Vec<u8>> {
+        (**self).pull()
+    }
+}
+
+/// connection of a party to one of its peers
+pub struct Peer<'a, const N: usize> {
+    party: &'a MultiParty<N>,
+    id: usize,
+}
+
+impl<const N: usize> Channel for Peer<'_, N> {
+    fn push(&self, msg: &[u8]) -> Result<()> {
+        self.party.send(&self.id, msg)
+    }
+
+    fn pull(&self) -> Result<Vec<u8>> {
+        self.party.recv(&self.id)
+    }
+}
+
 /// A session is a list of sockets between the current peer to others, plus a listener for incoming connections.
 struct Session<const N: usize> {
     sockets: [Option<TcpStream>; N],
@@ -92,6 +144,7 @@ impl<const N: usize> Session<N> {
                     let listener = TcpListener::bind(peers[my_id]).unwrap();
                     while slots > 0 {
                         let (mut socket, _) = listener.accept().unwrap();
+                        socket.set_nodelay(true).unwrap();
                         // hack: authentication, now simply by admitting whom they claimed to be (id)
                         let mut buf = [0u8; 8];
                         socket.read_exact(&mut buf).unwrap();
@@ -113,14 +166,19 @@ impl<const N: usize> Session<N> {
             for id in my_id + 1..N {
                 let sockets = Arc::clone(&sockets);
                 threads.push(scope.spawn(move || {
-                    let mut socket: TcpStream;
-                    if let Ok(stream) = TcpStream::connect(peers[id]) {
-                        socket = stream;
-                    } else {
-                        // hack: retry if connection fails
-                        thread::sleep(Duration::from_millis(10));
-                        socket = TcpStream::connect(peers[id]).unwrap();
-                    }
+                    // hack: retry while the peer is not yet listening
+                    let mut retries = 0;
+                    let mut socket = loop {
+                        match TcpStream::connect(peers[id]) {
+                            Ok(stream) => break stream,
+                            Err(err) if retries >= 100 => panic!("{err}"),
+                            Err(_) => {
+                                retries += 1;
+                                thread::sleep(Duration::from_millis(10));
+                            }
+                        }
+                    };
+                    socket.set_nodelay(true).unwrap();
                     // hack: authentication, now simply by sending who I am (my_id)
                     let mut buf = [0u8; 8];
                     socket.write_all(my_id.to_le_bytes().as_ref()).unwrap();
@@ -145,31 +203,72 @@ impl<const N: usize> Session<N> {
         })
     }
 
-    /// send `data` to peer `id`
+    /// send `data` to peer `id`, prefixed by its length
     pub fn send(&mut self, id: usize, data: &[u8]) -> Result<()> {
         self.sockets[id]
             .as_mut()
-            .map_or(Ok(()), |socket| Ok(socket.write_all(data)?))
+            .map_or(Ok(()), |socket| Ok(socket.write_all(&frame(data))?))
     }
 
-    /// receive from peer `id`
+    /// receive a whole message from peer `id`, which must not exceed `MAX_MESSAGE`
     pub fn recv(&mut self, id: usize) -> Result<Vec<u8>> {
-        // todo: handle large message, possibly use BufReader
-        let mut buf = vec![0; 1024];
-        let len = self.sockets[id]
-            .as_mut()
-            .map_or(Ok(0), |socket| socket.read(&mut buf));
-        buf.truncate(len?);
-        Ok(buf)
+        match self.sockets[id].as_mut() {
+            Some(socket) => {
+                let mut len = [0u8; 8];
+                socket.read_exact(&mut len)?;
+                let len = u64::from_le_bytes(len);
+                if len > MAX_MESSAGE as u64 {
+                    return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+                }
+                let mut buf = vec![0; len as usize];
+                socket.read_exact(&mut buf)?;
+                Ok(buf)
+            }
+            None => Ok(vec![]),
+        }
     }
 
     /// send `data` to all peers
     pub fn broadcast(&mut self, data: &[u8]) -> Result<()> {
+        let data = frame(data);
         thread::scope(|scope| {
             self.sockets.iter_mut().for_each(|socket| {
-                scope.spawn(|| socket.as_mut().map_or(Ok(()), |sock| sock.write_all(data)));
+                scope.spawn(|| socket.as_mut().map_or(Ok(()), |sock| sock.write_all(&data)));
             });
         });
         Ok(())
     }
 }
+
+/// prefixes `data` with its length, so that messages keep their boundaries
+fn frame(data: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(data.len() + 8);
+    buf.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    buf.extend_from_slice(data);
+    buf
+}
+
+#[test]
+fn test_correctness() {
+    use std::thread;
+
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9240 + i as u16)));
+    thread::scope(|scope| {
+        let sender = scope.spawn(move || {
+            let party = TwoParty::new(0, &peers).unwrap();
+            party.send(&1, b"hello").unwrap();
+            // a length beyond the limit is rejected before anything is allocated
+            let mut session = party.session.borrow_mut();
+            let socket = session.sockets[1].as_mut().unwrap();
+            socket.write_all(&u64::MAX.to_le_bytes()).unwrap();
+        });
+        let party = TwoParty::new(1, &peers).unwrap();
+        assert_eq!(b"hello".to_vec(), party.recv(&0).unwrap());
+        assert!(matches!(
+            party.recv(&0),
+            Err(Error::MPCError(MPCErrorKind::MalformedMessage))
+        ));
+        sender.join().unwrap();
+    });
+}
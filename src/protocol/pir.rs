@@ -0,0 +1,89 @@
+//! implements private information retrieval, where a client reads a record
+//! of a server's database without the server learning which one
+//! Private Information Retrieval
+//! https://dl.acm.org/doi/10.1145/293347.293350
+//! A database holds the values of a public list of Store keys, padded to
+//! records of equal width, and clients query records by their index in the list.
+
+use super::error::{Error, MPCErrorKind, Result};
+use crate::store::Store;
+use std::io::ErrorKind;
+
+pub mod gi14;
+pub mod hhcmv23;
+pub mod keyword;
+
+/// bytes prefixed to each record for its length
+const PREFIX: usize = 4;
+
+/// database of records padded to a common width
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Database {
+    records: Vec<Vec<u8>>,
+    width: usize,
+}
+
+/// bytes exchanged by one query, besides the one-time setup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sizes {
+    pub query: usize,
+    pub answer: usize,
+}
+
+impl Database {
+    /// pads the values into records prefixed by their length
+    pub fn new(values: &[Vec<u8>]) -> Self {
+        let width = PREFIX + values.iter().map(Vec::len).max().unwrap_or(0);
+        let records = values
+            .iter()
+            .map(|value| {
+                let mut record = (value.len() as u32).to_le_bytes().to_vec();
+                record.extend(value);
+                record.resize(width, 0);
+                record
+            })
+            .collect();
+        Self { records, width }
+    }
+
+    /// reads the values of the keys, which fails if a key is missing
+    pub fn from_store(store: &impl Store, keys: &[String]) -> Result<Self> {
+        let values = keys
+            .iter()
+            .map(|key| store.get::<Vec<u8>>(key))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::IOError(ErrorKind::NotFound))?;
+        Ok(Self::new(&values))
+    }
+
+    /// number of records
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// bytes of each record including the length prefix
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// gets the padded record at the index
+    pub fn record(&self, index: usize) -> &[u8] {
+        &self.records[index]
+    }
+}
+
+/// strips the padding from a record
+pub fn unpad(record: &[u8]) -> Result<Vec<u8>> {
+    let (prefix, value) = record
+        .split_first_chunk::<PREFIX>()
+        .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))?;
+    let len = u32::from_le_bytes(*prefix) as usize;
+    if len > value.len() {
+        return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+    }
+    Ok(value[..len].to_vec())
+}
@@ -0,0 +1,173 @@
+//! implements two-server PIR from distributed point functions
+//! Distributed Point Functions and Their Applications
+//! https://link.springer.com/chapter/10.1007/978-3-642-55220-5_35
+//! Parties 0 and 1 are non-colluding servers holding copies of the database
+//! and party 2 is the client. The client shares the indicator of its index
+//! into two DPF keys, each server XORs the records selected by its share, and
+//! the XOR of both answers is the record.
+
+use super::{unpad, Database, Sizes};
+use crate::protocol::dpf::{generate, Key};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::MultiParty;
+use crate::store::StoreValue;
+use std::io::ErrorKind;
+
+/// id of the client
+const CLIENT: usize = 2;
+
+/// bits of the DPF domain covering `count` records
+fn domain_bits(count: usize) -> u32 {
+    count.max(1).next_power_of_two().trailing_zeros()
+}
+
+/// bytes of a DPF key without output correction
+fn key_size(bits: u32) -> usize {
+    2 + 16 + 17 * bits as usize
+}
+
+/// holds a copy of the database and answers queries
+pub struct Server<'a> {
+    party: MultiParty<3>,
+    database: &'a Database,
+}
+
+impl<'a> Server<'a> {
+    pub fn new(party: MultiParty<3>, database: &'a Database) -> Self {
+        assert!(party.id < CLIENT, "servers are parties 0 and 1");
+        Self { party, database }
+    }
+
+    /// sends the number of records and their width to the client
+    pub fn setup(&self) -> Result<()> {
+        let header = [self.database.len(), self.database.width()].map(|x| (x as u64).to_le_bytes());
+        self.party.send(&CLIENT, &header.concat())
+    }
+
+    /// answers one query with the XOR of the records selected by the key
+    pub fn answer(&self) -> Result<()> {
+        let key = Key::from_bytes(&self.party.recv(&CLIENT)?)
+            .map_err(|_| Error::MPCError(MPCErrorKind::MalformedMessage))?;
+        if key.bits() != domain_bits(self.database.len()) {
+            return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+        }
+        let mut answer = vec![0u8; self.database.width()];
+        for (index, selected) in key.indicator().into_iter().enumerate() {
+            if selected && index < self.database.len() {
+                let record = self.database.record(index);
+                answer.iter_mut().zip(record).for_each(|(a, r)| *a ^= r);
+            }
+        }
+        self.party.send(&CLIENT, &answer)
+    }
+}
+
+/// queries records by index without revealing it to either server
+pub struct Client {
+    party: MultiParty<3>,
+    count: usize,
+    width: usize,
+}
+
+impl Client {
+    /// receives the setup from both servers, which must agree
+    pub fn setup(party: MultiParty<3>) -> Result<Self> {
+        assert_eq!(CLIENT, party.id, "the client is party 2");
+        let header = party.recv(&0)?;
+        if header.len() != 16 || header != party.recv(&1)? {
+            return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+        }
+        let [count, width] =
+            [0, 8].map(|i| u64::from_le_bytes(header[i..i + 8].try_into().unwrap()) as usize);
+        Ok(Self {
+            party,
+            count,
+            width,
+        })
+    }
+
+    /// number of records in the database
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// retrieves the value of the record at the index, which must be below `len`
+    pub fn query(&self, index: usize) -> Result<Vec<u8>> {
+        if index >= self.count {
+            return Err(Error::IOError(ErrorKind::InvalidInput));
+        }
+        let keys = generate(index, domain_bits(self.count), &[]);
+        for (id, key) in keys.into_iter().enumerate() {
+            self.party.send(&id, &key.to_bytes())?;
+        }
+        let mut record = vec![0u8; self.width];
+        for id in 0..CLIENT {
+            let answer = self.party.recv(&id)?;
+            if answer.len() != self.width {
+                return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+            }
+            record.iter_mut().zip(answer).for_each(|(r, a)| *r ^= a);
+        }
+        unpad(&record)
+    }
+
+    /// sizes summed over both servers
+    pub fn sizes(&self) -> Sizes {
+        Sizes {
+            query: 2 * key_size(domain_bits(self.count)),
+            answer: 2 * self.width,
+        }
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::store::{disk::DiskStore, Store};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let keys: Vec<String> = (0..300).map(|i| format!("item{i}")).collect();
+    let mut store = DiskStore::new("data/Alice", "records").unwrap();
+    for (i, key) in keys.iter().enumerate() {
+        store.set(key, &(i as u64 * 7919)).unwrap();
+    }
+    let database = Database::from_store(&store, &keys).unwrap();
+
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9110 + i as u16)));
+    let indices = [0, 255, 256, 299];
+    let database = &database;
+    thread::scope(|scope| {
+        for id in 0..2 {
+            scope.spawn(move || {
+                let server = Server::new(MultiParty::new(id, &peers).unwrap(), database);
+                server.setup().unwrap();
+                for _ in indices {
+                    server.answer().unwrap();
+                }
+            });
+        }
+        scope.spawn(move || {
+            let client = Client::setup(MultiParty::new(2, &peers).unwrap()).unwrap();
+            assert_eq!(300, client.len());
+            assert!(client.query(300).is_err());
+            for index in indices {
+                let value = client.query(index).unwrap();
+                assert_eq!(index as u64 * 7919, u64::from_bytes(&value).unwrap());
+            }
+            // keys grow with the logarithm of the database
+            let sizes = client.sizes();
+            assert_eq!(
+                Sizes {
+                    query: 2 * (18 + 17 * 9),
+                    answer: 24
+                },
+                sizes
+            );
+        });
+    });
+}
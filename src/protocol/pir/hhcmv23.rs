@@ -0,0 +1,327 @@
+//! implements SimplePIR, single-server PIR from learning with errors
+//! One Server for the Price of Two: Simple and Fast Single-Server Private Information Retrieval
+//! https://eprint.iacr.org/2022/949.pdf
+//! The database is a matrix of bytes whose columns hold records. The client
+//! encrypts the unit vector of a column under LWE modulo 2^32, and the server
+//! multiplies the matrix by it. A hint, the database times the public LWE
+//! matrix, is downloaded once in the setup and removes the mask from answers.
+
+use super::{unpad, Database, Sizes};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::{Channel, TwoParty, MAX_MESSAGE};
+use blake2::{Blake2b512, Digest};
+use std::io::ErrorKind;
+
+/// dimension of the LWE secret
+const DIMENSION: usize = 1024;
+/// scale of a plaintext byte modulo 2^32
+const DELTA: u32 = 1 << 24;
+/// columns at most, so that the noise `255 * 32 * columns` stays below `DELTA / 2`
+const MAX_COLUMNS: usize = 1024;
+
+/// samples noise of the centered binomial distribution over `[-32, 32]`
+fn noise() -> u32 {
+    OsRng
+        .next_u32()
+        .count_ones()
+        .wrapping_sub(OsRng.next_u32().count_ones())
+}
+
+/// expands the public LWE matrix of `columns` rows from a seed
+fn expand(seed: &[u8; 32], columns: usize) -> Vec<u32> {
+    let mut matrix = Vec::with_capacity(columns * DIMENSION);
+    for row in 0..columns as u64 {
+        for block in 0..(DIMENSION / 16) as u64 {
+            let mut hasher = Blake2b512::new();
+            hasher.update(seed);
+            hasher.update(row.to_le_bytes());
+            hasher.update(block.to_le_bytes());
+            let hash = hasher.finalize();
+            matrix.extend(
+                hash.chunks_exact(4)
+                    .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap())),
+            );
+        }
+    }
+    matrix
+}
+
+/// inner product modulo 2^32
+fn dot(a: &[u32], b: &[u32]) -> u32 {
+    a.iter()
+        .zip(b)
+        .fold(0u32, |acc, (a, b)| acc.wrapping_add(a.wrapping_mul(*b)))
+}
+
+fn encode_words(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+fn decode_words(bytes: &[u8], count: usize) -> Result<Vec<u32>> {
+    if bytes.len() != count * 4 {
+        return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+/// shape of the database matrix, where the record at index `i` fills the rows of
+/// block `i / columns` in column `i % columns`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Shape {
+    count: usize,
+    width: usize,
+    columns: usize,
+}
+
+impl Shape {
+    /// balances rows and columns
+    fn new(count: usize, width: usize) -> Self {
+        let columns = ((count * width) as f64).sqrt().ceil() as usize;
+        Self {
+            count,
+            width,
+            columns: columns.clamp(1, count.clamp(1, MAX_COLUMNS)),
+        }
+    }
+
+    fn rows(&self) -> usize {
+        self.count.div_ceil(self.columns) * self.width
+    }
+
+    fn sizes(&self) -> Sizes {
+        Sizes {
+            query: self.columns * 4,
+            answer: self.rows() * 4,
+        }
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        [self.count, self.width, self.columns]
+            .iter()
+            .flat_map(|x| (*x as u64).to_le_bytes())
+            .collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let words = decode_words(bytes, 6)?;
+        let [count, width, columns] =
+            [0, 2, 4].map(|i| (words[i] as u64 | (words[i + 1] as u64) << 32) as usize);
+        // the hint of `rows * DIMENSION` words must fit into one message
+        let hint = count
+            .div_ceil(columns.max(1))
+            .checked_mul(width)
+            .and_then(|rows| rows.checked_mul(DIMENSION * 4));
+        if columns == 0
+            || columns > MAX_COLUMNS
+            || columns > count.max(1)
+            || width == 0
+            || hint.is_none_or(|hint| hint > MAX_MESSAGE)
+        {
+            return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+        }
+        Ok(Self {
+            count,
+            width,
+            columns,
+        })
+    }
+}
+
+/// holds the database as a matrix of bytes and answers queries
+pub struct Server<C = TwoParty> {
+    party: C,
+    shape: Shape,
+    /// rows of the database matrix
+    matrix: Vec<u8>,
+}
+
+impl<C: Channel> Server<C> {
+    pub fn new(party: C, database: &Database) -> Self {
+        let shape = Shape::new(database.len(), database.width());
+        let mut matrix = vec![0u8; shape.rows() * shape.columns];
+        for index in 0..database.len() {
+            let (block, column) = (index / shape.columns, index % shape.columns);
+            for (byte, &value) in database.record(index).iter().enumerate() {
+                matrix[(block * shape.width + byte) * shape.columns + column] = value;
+            }
+        }
+        Self {
+            party,
+            shape,
+            matrix,
+        }
+    }
+
+    /// sends the shape, the seed of the LWE matrix and the hint, which costs
+    /// 4 bytes per row and LWE dimension, once for all queries
+    pub fn setup(&self) -> Result<()> {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        let lwe = expand(&seed, self.shape.columns);
+        let mut hint = vec![0u32; self.shape.rows() * DIMENSION];
+        for (row, hint) in hint.chunks_exact_mut(DIMENSION).enumerate() {
+            let entries = &self.matrix[row * self.shape.columns..(row + 1) * self.shape.columns];
+            for (&entry, lwe) in entries.iter().zip(lwe.chunks_exact(DIMENSION)) {
+                for (h, a) in hint.iter_mut().zip(lwe) {
+                    *h = h.wrapping_add(a.wrapping_mul(entry as u32));
+                }
+            }
+        }
+        self.party
+            .push(&[self.shape.to_bytes(), seed.to_vec()].concat())?;
+        self.party.push(&encode_words(&hint))
+    }
+
+    /// answers one query by multiplying the database with it
+    pub fn answer(&self) -> Result<()> {
+        let query = decode_words(&self.party.pull()?, self.shape.columns)?;
+        let answer: Vec<u32> = self
+            .matrix
+            .chunks_exact(self.shape.columns)
+            .map(|row| {
+                row.iter().zip(&query).fold(0u32, |acc, (&d, q)| {
+                    acc.wrapping_add(q.wrapping_mul(d as u32))
+                })
+            })
+            .collect();
+        self.party.push(&encode_words(&answer))
+    }
+
+    pub fn sizes(&self) -> Sizes {
+        self.shape.sizes()
+    }
+}
+
+/// queries records by index without revealing it
+pub struct Client<C = TwoParty> {
+    party: C,
+    shape: Shape,
+    lwe: Vec<u32>,
+    hint: Vec<u32>,
+}
+
+impl<C: Channel> Client<C> {
+    /// receives the setup from the server
+    pub fn setup(party: C) -> Result<Self> {
+        let header = party.pull()?;
+        let (shape, seed) = header
+            .split_at_checked(24)
+            .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))?;
+        let shape = Shape::from_bytes(shape)?;
+        let seed: [u8; 32] = seed
+            .try_into()
+            .map_err(|_| Error::MPCError(MPCErrorKind::MalformedMessage))?;
+        let hint = decode_words(&party.pull()?, shape.rows() * DIMENSION)?;
+        Ok(Self {
+            party,
+            shape,
+            lwe: expand(&seed, shape.columns),
+            hint,
+        })
+    }
+
+    /// number of records in the database
+    pub fn len(&self) -> usize {
+        self.shape.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shape.count == 0
+    }
+
+    /// retrieves the value of the record at the index, which must be below `len`
+    pub fn query(&self, index: usize) -> Result<Vec<u8>> {
+        if index >= self.shape.count {
+            return Err(Error::IOError(ErrorKind::InvalidInput));
+        }
+        let (block, column) = (index / self.shape.columns, index % self.shape.columns);
+        let secret: Vec<u32> = (0..DIMENSION).map(|_| OsRng.next_u32()).collect();
+        let query: Vec<u32> = self
+            .lwe
+            .chunks_exact(DIMENSION)
+            .enumerate()
+            .map(|(j, a)| {
+                let message = if j == column { DELTA } else { 0 };
+                dot(a, &secret).wrapping_add(noise()).wrapping_add(message)
+            })
+            .collect();
+        self.party.push(&encode_words(&query))?;
+        let answer = decode_words(&self.party.pull()?, self.shape.rows())?;
+        let rows = block * self.shape.width..(block + 1) * self.shape.width;
+        let record: Vec<u8> = rows
+            .map(|row| {
+                let mask = dot(&self.hint[row * DIMENSION..(row + 1) * DIMENSION], &secret);
+                // rounds to the nearest multiple of DELTA
+                (answer[row].wrapping_sub(mask).wrapping_add(DELTA / 2) >> 24) as u8
+            })
+            .collect();
+        unpad(&record)
+    }
+
+    pub fn sizes(&self) -> Sizes {
+        self.shape.sizes()
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::store::{disk::DiskStore, Store};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let keys: Vec<String> = (0..100).map(|i| format!("user{i}")).collect();
+    let mut store = DiskStore::new("data/Alice", "pir").unwrap();
+    for (i, key) in keys.iter().enumerate() {
+        store.set(key, &"x".repeat(i % 13).to_string()).unwrap();
+    }
+    let database = Database::from_store(&store, &keys).unwrap();
+    assert_eq!(16, database.width());
+    assert!(Database::from_store(&store, &["missing".to_string()]).is_err());
+    // a shape whose hint overflows or exceeds a message is rejected
+    let shape = Shape::new(100, 16);
+    assert_eq!(Ok(shape), Shape::from_bytes(&shape.to_bytes()));
+    for (count, width, columns) in [(usize::MAX, usize::MAX, 1), (1 << 40, 1, 1024), (1, 1, 2)] {
+        let shape = Shape {
+            count,
+            width,
+            columns,
+        };
+        assert!(Shape::from_bytes(&shape.to_bytes()).is_err());
+    }
+
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9100 + i as u16)));
+    let indices = [0, 38, 99];
+    thread::scope(|scope| {
+        let database = &database;
+        scope.spawn(move || {
+            let server = Server::new(TwoParty::new(0, &peers).unwrap(), database);
+            server.setup().unwrap();
+            for _ in indices {
+                server.answer().unwrap();
+            }
+        });
+        scope.spawn(move || {
+            let client = Client::setup(TwoParty::new(1, &peers).unwrap()).unwrap();
+            assert_eq!(100, client.len());
+            assert!(client.query(100).is_err());
+            for index in indices {
+                let value = client.query(index).unwrap();
+                assert_eq!("x".repeat(index % 13).into_bytes(), value);
+            }
+            // the sizes grow with the square root of the database
+            let sizes = client.sizes();
+            assert_eq!(
+                Sizes {
+                    query: 160,
+                    answer: 192
+                },
+                sizes
+            );
+        });
+    });
+}
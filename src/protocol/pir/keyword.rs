@@ -0,0 +1,218 @@
+//! implements keyword PIR, where a client looks up a Store key without revealing it
+//! Private Information Retrieval by Keywords
+//! https://eprint.iacr.org/1998/003
+//! The server places the OPRF outputs of its keys into a cuckoo table, where
+//! each bin holds a tag of the output and the value encrypted under it. The
+//! client obtains the output of its key by the OPRF, and reads all candidate
+//! bins by SimplePIR, so that the server learns neither the key nor whether it
+//! exists, and the client can only open the value of its key.
+
+use super::{hhcmv23, Database};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::oprf::{self, Output};
+use crate::protocol::party::TwoParty;
+use crate::protocol::psi::{dedup, Cuckoo, HASHES};
+use crate::store::Store;
+use blake2::{Blake2b512, Digest};
+use k256::ProjectivePoint;
+use std::io::ErrorKind;
+
+/// bytes of the tag that identifies the bin of a key
+const TAG: usize = 16;
+
+fn tag(output: &Output) -> [u8; TAG] {
+    let hash = Blake2b512::new()
+        .chain_update(b"tag")
+        .chain_update(output)
+        .finalize();
+    hash[..TAG].try_into().unwrap()
+}
+
+/// encrypts or decrypts a value with a keystream derived from the output
+fn cipher(output: &Output, value: &[u8]) -> Vec<u8> {
+    let mut bytes = value.to_vec();
+    for (counter, chunk) in bytes.chunks_mut(64).enumerate() {
+        let stream = Blake2b512::new()
+            .chain_update(b"value")
+            .chain_update(output)
+            .chain_update((counter as u64).to_le_bytes())
+            .finalize();
+        chunk.iter_mut().zip(stream).for_each(|(b, s)| *b ^= s);
+    }
+    bytes
+}
+
+/// holds a store and answers lookups over a borrowed party
+pub struct Server<'a> {
+    party: &'a TwoParty,
+    seed: [u8; 16],
+    bins: usize,
+    oprf: oprf::Server<&'a TwoParty>,
+    pir: hhcmv23::Server<&'a TwoParty>,
+}
+
+impl<'a> Server<'a> {
+    /// reads the values of the keys, which fails if a key is missing, and
+    /// builds the cuckoo table under the OPRF key, where repeated keys are kept once
+    pub fn new(
+        party: &'a TwoParty,
+        store: &impl Store,
+        keys: &[String],
+        key: oprf::Key,
+    ) -> Result<Self> {
+        let keys = dedup(keys);
+        let values = keys
+            .iter()
+            .map(|key| store.get::<Vec<u8>>(key))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::IOError(ErrorKind::NotFound))?;
+        let outputs: Vec<Output> = keys.iter().map(|k| key.evaluate(k.as_bytes())).collect();
+        let items: Vec<&[u8]> = outputs.iter().map(|output| output.as_slice()).collect();
+        let bins = (keys.len() * 4 / 3).max(4);
+        let cuckoo = Cuckoo::new(&items, bins);
+        let records: Vec<Vec<u8>> = cuckoo
+            .bins
+            .iter()
+            .map(|bin| match *bin {
+                Some((item, _)) => {
+                    let output = &outputs[item];
+                    [tag(output).to_vec(), cipher(output, &values[item])].concat()
+                }
+                None => vec![],
+            })
+            .collect();
+        Ok(Self {
+            party,
+            seed: cuckoo.seed,
+            bins,
+            oprf: oprf::Server::new(party, key),
+            pir: hhcmv23::Server::new(party, &Database::new(&records)),
+        })
+    }
+
+    /// sends the hash functions of the table and the setup of SimplePIR, once for all lookups
+    pub fn setup(&self) -> Result<()> {
+        self.party
+            .push(&[self.seed.as_slice(), &(self.bins as u64).to_le_bytes()].concat())?;
+        self.pir.setup()
+    }
+
+    /// answers one lookup
+    pub fn answer(&self) -> Result<()> {
+        self.oprf.evaluate()?;
+        for _ in 0..HASHES {
+            self.pir.answer()?;
+        }
+        Ok(())
+    }
+}
+
+/// looks up keys over a borrowed party, verifying the OPRF against the server's public key
+pub struct Client<'a> {
+    seed: [u8; 16],
+    bins: usize,
+    oprf: oprf::Client<&'a TwoParty>,
+    pir: hhcmv23::Client<&'a TwoParty>,
+}
+
+impl<'a> Client<'a> {
+    /// receives the setup from the server
+    pub fn setup(party: &'a TwoParty, public: ProjectivePoint) -> Result<Self> {
+        let malformed = || Error::MPCError(MPCErrorKind::MalformedMessage);
+        let header = party.pull()?;
+        let (seed, bins) = header.split_first_chunk::<16>().ok_or_else(malformed)?;
+        let bins = u64::from_le_bytes(bins.try_into().map_err(|_| malformed())?) as usize;
+        if bins == 0 {
+            return Err(malformed());
+        }
+        let pir = hhcmv23::Client::setup(party)?;
+        if pir.len() != bins {
+            return Err(malformed());
+        }
+        Ok(Self {
+            seed: *seed,
+            bins,
+            oprf: oprf::Client::new(party, public),
+            pir,
+        })
+    }
+
+    /// retrieves the value of the key, or None if the server does not hold it.
+    /// Every lookup reads all candidate bins, so that it looks the same to the server.
+    pub fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let output = self.oprf.evaluate(&[key.as_bytes()])?[0];
+        let mut value = None;
+        for bin in Cuckoo::candidates(&self.seed, &output, self.bins) {
+            let record = self.pir.query(bin)?;
+            if let Some((found, cipher_text)) = record.split_first_chunk::<TAG>() {
+                if *found == tag(&output) {
+                    value = Some(cipher(&output, cipher_text));
+                }
+            }
+        }
+        Ok(value)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::store::disk::DiskStore;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let keys: Vec<String> = (0..60).map(|i| format!("user:{i}")).collect();
+    let mut store = DiskStore::new("data/Alice", "directory").unwrap();
+    for (i, key) in keys.iter().enumerate() {
+        store.set(key, &format!("{i}@example.com")).unwrap();
+    }
+    let key = oprf::Key::random();
+    let public = key.public();
+    let lookups = ["user:42", "user:0", "user:60", "admin"];
+
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9190 + i as u16)));
+    thread::scope(|scope| {
+        let (store, keys) = (&store, &keys);
+        scope.spawn(move || {
+            let party = TwoParty::new(0, &peers).unwrap();
+            let missing = ["missing".to_string()];
+            assert!(Server::new(&party, store, &missing, key.clone()).is_err());
+            // copies of a key share their candidate bins, and are kept once
+            let copies = vec![keys[0].clone(); 4];
+            assert!(Server::new(&party, store, &copies, key.clone()).is_ok());
+            let server = Server::new(&party, store, keys, key).unwrap();
+            server.setup().unwrap();
+            for _ in lookups {
+                server.answer().unwrap();
+            }
+        });
+        scope.spawn(move || {
+            let party = TwoParty::new(1, &peers).unwrap();
+            let client = Client::setup(&party, public).unwrap();
+            let values: Vec<Option<Vec<u8>>> =
+                lookups.iter().map(|key| client.get(key).unwrap()).collect();
+            assert_eq!(
+                vec![
+                    Some(b"42@example.com".to_vec()),
+                    Some(b"0@example.com".to_vec()),
+                    None,
+                    None
+                ],
+                values
+            );
+        });
+    });
+
+    // a table of no bins is rejected
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9260 + i as u16)));
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            let party = TwoParty::new(0, &peers).unwrap();
+            // a zero seed followed by zero bins
+            party.push(&[0u8; 24]).unwrap();
+        });
+        let party = TwoParty::new(1, &peers).unwrap();
+        assert!(Client::setup(&party, public).is_err());
+    });
+}
@@ -0,0 +1,136 @@
+//! implements private set intersection, where a receiver learns which of its
+//! items a sender also holds and nothing else about the sender's set
+//! Scalable Private Set Intersection Based on OT Extension
+//! https://eprint.iacr.org/2016/930.pdf
+//! Items are strings, e.g., customer ids, given directly or read from a Store.
+//! Party 0 is the sender and party 1 is the receiver.
+
+use super::error::{Error, MPCErrorKind, Result};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::math::weierstrass::{decode_points, hash_to_curve};
+use crate::store::Store;
+use blake2::{Blake2b512, Digest};
+use k256::{ProjectivePoint, Scalar};
+use std::io::ErrorKind;
+
+pub mod hfh99;
+pub mod kkrt16;
+pub mod sum;
+pub mod union;
+
+/// reads the string values of the keys, which fails if a key is missing
+pub fn from_store(store: &impl Store, keys: &[String]) -> Result<Vec<String>> {
+    keys.iter()
+        .map(|key| store.get::<String>(key))
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| Error::IOError(ErrorKind::NotFound))
+}
+
+/// removes duplicates, keeping the first occurrence of each item
+pub(crate) fn dedup(set: &[String]) -> Vec<String> {
+    let mut seen = std::collections::HashSet::new();
+    set.iter()
+        .filter(|item| seen.insert(item.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// hashes the items to points to the power of the key, which is a commutative encryption
+pub(crate) fn blind(set: &[String], key: &Scalar) -> Vec<ProjectivePoint> {
+    set.iter()
+        .map(|item| hash_to_curve(item.as_bytes()) * key)
+        .collect()
+}
+
+/// decodes points received from the other party
+pub(crate) fn decode(bytes: &[u8]) -> Result<Vec<ProjectivePoint>> {
+    decode_points(bytes).ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))
+}
+
+/// concatenates byte strings, each prefixed by its length
+pub(crate) fn frame(items: &[Vec<u8>]) -> Vec<u8> {
+    items
+        .iter()
+        .flat_map(|item| [(item.len() as u32).to_le_bytes().as_slice(), item].concat())
+        .collect()
+}
+
+/// splits a concatenation of `frame` into its byte strings
+pub(crate) fn unframe(mut bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut items = vec![];
+    while let Some((len, rest)) = bytes.split_first_chunk::<4>() {
+        let len = u32::from_le_bytes(*len) as usize;
+        let item = rest
+            .get(..len)
+            .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))?;
+        items.push(item.to_vec());
+        bytes = &rest[len..];
+    }
+    if !bytes.is_empty() {
+        return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+    }
+    Ok(items)
+}
+
+/// permutes the items uniformly at random
+pub(crate) fn shuffle<T>(items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, (OsRng.next_u64() % (i as u64 + 1)) as usize);
+    }
+}
+
+/// number of hash functions of cuckoo hashing
+pub const HASHES: usize = 3;
+
+/// cuckoo hash table, where each item sits in one of its candidate bins
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cuckoo {
+    /// seed of the hash functions
+    pub seed: [u8; 16],
+    /// index of the item and of its hash function, for each bin
+    pub bins: Vec<Option<(usize, usize)>>,
+}
+
+impl Cuckoo {
+    /// inserts the items into `bins` bins, which should be about 1.3 times as many,
+    /// with fresh hash functions until all of them fit
+    pub fn new(items: &[&[u8]], bins: usize) -> Self {
+        assert!(items.len() <= bins, "too few bins");
+        'retry: loop {
+            let mut seed = [0u8; 16];
+            OsRng.fill_bytes(&mut seed);
+            let mut table = vec![None; bins];
+            for index in 0..items.len() {
+                let mut current = (index, 0);
+                let placed = (0..100 + 10 * items.len().ilog2() as usize).any(|_| {
+                    let (item, function) = current;
+                    let bin = Self::candidates(&seed, items[item], bins)[function];
+                    match table[bin].replace(current) {
+                        None => true,
+                        // the evicted item moves to its next candidate
+                        Some((evicted, function)) => {
+                            current = (evicted, (function + 1) % HASHES);
+                            false
+                        }
+                    }
+                });
+                if !placed {
+                    continue 'retry;
+                }
+            }
+            return Self { seed, bins: table };
+        }
+    }
+
+    /// bins of the item under each hash function
+    pub fn candidates(seed: &[u8; 16], item: &[u8], bins: usize) -> [usize; HASHES] {
+        let hash = Blake2b512::new()
+            .chain_update(seed)
+            .chain_update(item)
+            .finalize();
+        std::array::from_fn(|i| {
+            (u64::from_le_bytes(hash[8 * i..8 * (i + 1)].try_into().unwrap()) % bins as u64)
+                as usize
+        })
+    }
+}
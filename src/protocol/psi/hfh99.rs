@@ -0,0 +1,136 @@
+//! implements Diffie-Hellman based PSI on secp256k1
+//! Enhancing Privacy and Trust in Electronic Communities
+//! https://dl.acm.org/doi/10.1145/336992.337012
+//! The receiver sends its hashed items to the power `a`, and the sender returns
+//! them to the power `b` with its own hashed items to the power `b`. Since
+//! exponentiation commutes, common items meet at `H(x)^(ab)`. In the cardinality
+//! variant the sender shuffles its reply, so that only the count is learned.
+
+use super::{blind, decode, dedup, shuffle};
+use crate::math::weierstrass::encode_points;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::TwoParty;
+use k256::{
+    elliptic_curve::{rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+use std::collections::HashSet;
+
+/// compressed encoding of a point, which is compared between the parties
+type Encoded = Vec<u8>;
+
+/// holds a set and learns only the size of the receiver's set
+pub struct Sender {
+    party: TwoParty,
+}
+
+impl Sender {
+    pub fn new(party: TwoParty) -> Self {
+        Self { party }
+    }
+
+    fn run(&self, set: &[String], hide_order: bool) -> Result<()> {
+        let key = Scalar::random(&mut OsRng);
+        let mut reply: Vec<ProjectivePoint> = decode(&self.party.pull()?)?
+            .into_iter()
+            .map(|point| point * key)
+            .collect();
+        if hide_order {
+            shuffle(&mut reply);
+        }
+        let mut own = blind(&dedup(set), &key);
+        shuffle(&mut own);
+        self.party.push(&encode_points(&reply))?;
+        self.party.push(&encode_points(&own))
+    }
+
+    /// lets the receiver learn the intersection
+    pub fn intersect(&self, set: &[String]) -> Result<()> {
+        self.run(set, false)
+    }
+
+    /// lets the receiver learn the size of the intersection
+    pub fn cardinality(&self, set: &[String]) -> Result<()> {
+        self.run(set, true)
+    }
+}
+
+/// holds a set and learns the intersection, or its size
+pub struct Receiver {
+    party: TwoParty,
+}
+
+impl Receiver {
+    pub fn new(party: TwoParty) -> Self {
+        Self { party }
+    }
+
+    /// gets the doubly blinded points of its items and the set of the sender's
+    fn run(&self, set: &[String]) -> Result<(Vec<Encoded>, HashSet<Encoded>)> {
+        let key = Scalar::random(&mut OsRng);
+        self.party.push(&encode_points(&blind(set, &key)))?;
+        let reply = decode(&self.party.pull()?)?;
+        if reply.len() != set.len() {
+            return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+        }
+        let others = decode(&self.party.pull()?)?
+            .into_iter()
+            .map(|point| encode_points(&[point * key]))
+            .collect();
+        let reply = reply.iter().map(|point| encode_points(&[*point])).collect();
+        Ok((reply, others))
+    }
+
+    /// computes the common items in the order of the given set
+    pub fn intersect(&self, set: &[String]) -> Result<Vec<String>> {
+        let set = dedup(set);
+        let (reply, others) = self.run(&set)?;
+        Ok(set
+            .into_iter()
+            .zip(reply)
+            .filter_map(|(item, point)| others.contains(&point).then_some(item))
+            .collect())
+    }
+
+    /// computes the number of common items
+    pub fn cardinality(&self, set: &[String]) -> Result<usize> {
+        let (reply, others) = self.run(&dedup(set))?;
+        Ok(reply.iter().filter(|point| others.contains(*point)).count())
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::store::{disk::DiskStore, Store};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let mut store = DiskStore::new("data/Alice", "customers").unwrap();
+    let keys: Vec<String> = (0..40).map(|i| format!("customer{i}")).collect();
+    for (i, key) in keys.iter().enumerate() {
+        store.set(key, &format!("id-{}", 3 * i)).unwrap();
+    }
+    let alice = super::from_store(&store, &keys).unwrap();
+    let bob: Vec<String> = (0..50).map(|i| format!("id-{}", 2 * i)).collect();
+    let expected: Vec<String> = (0..40)
+        .filter(|i| i % 2 == 0 && 3 * i < 100)
+        .map(|i| format!("id-{}", 3 * i))
+        .collect();
+
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9120 + i as u16)));
+    thread::scope(|scope| {
+        let bob = &bob;
+        scope.spawn(move || {
+            let sender = Sender::new(TwoParty::new(0, &peers).unwrap());
+            sender.intersect(bob).unwrap();
+            sender.cardinality(bob).unwrap();
+        });
+        let (alice, expected) = (&alice, &expected);
+        scope.spawn(move || {
+            let receiver = Receiver::new(TwoParty::new(1, &peers).unwrap());
+            assert_eq!(*expected, receiver.intersect(alice).unwrap());
+            assert_eq!(expected.len(), receiver.cardinality(alice).unwrap());
+        });
+    });
+}
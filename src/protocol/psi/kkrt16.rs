@@ -0,0 +1,221 @@
+//! implements PSI from the batched OPRF of Kolesnikov, Kumaresan, Rosulek and Trieu
+//! Efficient Batched Oblivious PRF with Applications to Private Set Intersection
+//! https://eprint.iacr.org/2016/799.pdf
+//! The receiver places its items into bins by cuckoo hashing and obtains one
+//! OPRF output per bin. The OPRF extends 512 base OTs, where the sender's
+//! choice bits are its key and a pseudorandom code of 512 bits encodes the
+//! inputs. The sender evaluates the OPRF on its items at each candidate bin and
+//! sends the outputs, which the receiver compares. Unlike the DH protocol, the
+//! cost grows with one hash per item instead of two exponentiations. The receiver
+//! sees which bins match, so only the DH protocol has a cardinality variant.
+
+use super::{dedup, shuffle, Cuckoo, HASHES};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::protocol::error::Result;
+use crate::protocol::ot::{bit, co15, malformed, prg, transpose, Choice, OTReceive, OTSend};
+use crate::protocol::party::{TwoParty, MAX_MESSAGE};
+use blake2::{Blake2b512, Digest};
+use std::collections::HashSet;
+
+/// bits of the pseudorandom code, which is the number of base OTs
+const WIDTH: usize = 512;
+/// bytes of an OPRF output sent for comparison
+const OUTPUT: usize = 16;
+/// bins at most, so that the masks of all base OTs fit into one message
+const MAX_BINS: usize = MAX_MESSAGE / WIDTH * 8;
+
+/// codeword of an item tagged by its hash function
+fn code(item: &[u8], function: usize) -> [u8; WIDTH / 8] {
+    Blake2b512::new()
+        .chain_update(b"code")
+        .chain_update([function as u8])
+        .chain_update(item)
+        .finalize()
+        .into()
+}
+
+/// hashes a row of the OT extension matrix with its bin into an OPRF output
+fn output(bin: usize, row: &[u8]) -> [u8; OUTPUT] {
+    let hash = Blake2b512::new()
+        .chain_update((bin as u64).to_le_bytes())
+        .chain_update(row)
+        .finalize();
+    hash[..OUTPUT].try_into().unwrap()
+}
+
+/// holds a set and learns only the size of the receiver's set
+pub struct Sender {
+    party: TwoParty,
+}
+
+impl Sender {
+    pub fn new(party: TwoParty) -> Self {
+        Self { party }
+    }
+
+    /// lets the receiver learn the intersection
+    pub fn intersect(&self, set: &[String]) -> Result<()> {
+        let header = self.party.pull()?;
+        let (seed, bins) = header.split_first_chunk::<16>().ok_or_else(malformed)?;
+        let bins = u64::from_le_bytes(bins.try_into().map_err(|_| malformed())?);
+        if bins == 0 || bins > MAX_BINS as u64 {
+            return Err(malformed());
+        }
+        let bins = bins as usize;
+        let len = bins.div_ceil(8);
+        // the key of the OPRF is the choice bits of the base OTs
+        let mut key = [0u8; WIDTH / 8];
+        OsRng.fill_bytes(&mut key);
+        let receiver = co15::Receiver(self.party.peer(1 - self.party.id));
+        let mut columns = Vec::with_capacity(WIDTH);
+        for j in 0..WIDTH {
+            let choice = Choice::<2>::new(bit(&key, j) as usize).unwrap();
+            let seed: [u8; 16] = receiver.receive(&choice)?;
+            columns.push(prg(&seed, len));
+        }
+        let masks = self.party.pull()?;
+        if masks.len() != WIDTH * len {
+            return Err(malformed());
+        }
+        for (j, (column, mask)) in columns.iter_mut().zip(masks.chunks_exact(len)).enumerate() {
+            if bit(&key, j) {
+                column.iter_mut().zip(mask).for_each(|(c, m)| *c ^= m);
+            }
+        }
+        let rows = transpose::<{ WIDTH / 8 }>(&columns, bins);
+        let set = dedup(set);
+        for function in 0..HASHES {
+            let mut outputs: Vec<[u8; OUTPUT]> = set
+                .iter()
+                .map(|item| {
+                    let bin = Cuckoo::candidates(seed, item.as_bytes(), bins)[function];
+                    let code = code(item.as_bytes(), function);
+                    let row: Vec<u8> = (0..WIDTH / 8)
+                        .map(|i| rows[bin][i] ^ (code[i] & key[i]))
+                        .collect();
+                    output(bin, &row)
+                })
+                .collect();
+            shuffle(&mut outputs);
+            self.party.push(&outputs.concat())?;
+        }
+        Ok(())
+    }
+}
+
+/// holds a set and learns the intersection
+pub struct Receiver {
+    party: TwoParty,
+}
+
+impl Receiver {
+    pub fn new(party: TwoParty) -> Self {
+        Self { party }
+    }
+
+    /// computes the common items in the order of the given set
+    pub fn intersect(&self, set: &[String]) -> Result<Vec<String>> {
+        let set = dedup(set);
+        let items: Vec<&[u8]> = set.iter().map(|item| item.as_bytes()).collect();
+        let bins = (set.len() * 4 / 3).max(4);
+        let cuckoo = Cuckoo::new(&items, bins);
+        let len = bins.div_ceil(8);
+        self.party
+            .push(&[cuckoo.seed.as_slice(), &(bins as u64).to_le_bytes()].concat())?;
+        // codewords of the items in their bins, and random ones for empty bins
+        let codes: Vec<[u8; WIDTH / 8]> = cuckoo
+            .bins
+            .iter()
+            .map(|bin| match *bin {
+                Some((item, function)) => code(items[item], function),
+                None => {
+                    let mut code = [0u8; WIDTH / 8];
+                    OsRng.fill_bytes(&mut code);
+                    code
+                }
+            })
+            .collect();
+        let sender = co15::Sender(self.party.peer(1 - self.party.id));
+        let mut columns = Vec::with_capacity(WIDTH);
+        let mut masks = Vec::with_capacity(WIDTH * len);
+        for j in 0..WIDTH {
+            let mut seeds = [[0u8; 16]; 2];
+            seeds.iter_mut().for_each(|seed| OsRng.fill_bytes(seed));
+            sender.send(&seeds)?;
+            let column = prg(&seeds[0], len);
+            let mut mask = prg(&seeds[1], len);
+            for (b, code) in codes.iter().enumerate() {
+                mask[b / 8] ^= (bit(code, j) as u8) << (b % 8);
+            }
+            mask.iter_mut().zip(&column).for_each(|(m, c)| *m ^= c);
+            masks.extend(mask);
+            columns.push(column);
+        }
+        self.party.push(&masks)?;
+        let rows = transpose::<{ WIDTH / 8 }>(&columns, bins);
+        let mut outputs = Vec::with_capacity(HASHES);
+        for _ in 0..HASHES {
+            let bytes = self.party.pull()?;
+            if bytes.len() % OUTPUT != 0 {
+                return Err(malformed());
+            }
+            let chunks = bytes.chunks_exact(OUTPUT);
+            outputs.push(chunks.map(|chunk| chunk.to_vec()).collect::<HashSet<_>>());
+        }
+        let mut common = vec![false; set.len()];
+        for (bin, entry) in cuckoo.bins.iter().enumerate() {
+            if let Some((item, function)) = *entry {
+                common[item] = outputs[function].contains(output(bin, &rows[bin]).as_slice());
+            }
+        }
+        Ok(set
+            .into_iter()
+            .zip(common)
+            .filter_map(|(item, common)| common.then_some(item))
+            .collect())
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let alice: Vec<String> = (0..500).map(|i| format!("id-{}", 3 * i)).collect();
+    let bob: Vec<String> = (0..700).map(|i| format!("id-{}", 2 * i)).collect();
+    let expected: Vec<String> = (0..500)
+        .filter(|i| i % 2 == 0 && 3 * i < 1400)
+        .map(|i| format!("id-{}", 3 * i))
+        .collect();
+
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9130 + i as u16)));
+    thread::scope(|scope| {
+        let bob = &bob;
+        scope.spawn(move || {
+            let sender = Sender::new(TwoParty::new(0, &peers).unwrap());
+            sender.intersect(bob).unwrap();
+        });
+        let (alice, expected) = (&alice, &expected);
+        scope.spawn(move || {
+            let receiver = Receiver::new(TwoParty::new(1, &peers).unwrap());
+            assert_eq!(*expected, receiver.intersect(alice).unwrap());
+        });
+    });
+
+    // headers of no bins or of more bins than fit into a message are rejected
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9250 + i as u16)));
+    thread::scope(|scope| {
+        scope.spawn(move || {
+            let party = TwoParty::new(1, &peers).unwrap();
+            for bins in [0, MAX_BINS as u64 + 1] {
+                let header = [[0u8; 16].as_slice(), &bins.to_le_bytes()].concat();
+                party.push(&header).unwrap();
+            }
+        });
+        let sender = Sender::new(TwoParty::new(0, &peers).unwrap());
+        assert_eq!(Err(malformed()), sender.intersect(&bob));
+        assert_eq!(Err(malformed()), sender.intersect(&bob));
+    });
+}
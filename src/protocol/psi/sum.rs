@@ -0,0 +1,175 @@
+//! implements private intersection-sum with cardinality
+//! On Deploying Secure Computing: Private Intersection-Sum-with-Cardinality
+//! https://eprint.iacr.org/2019/723.pdf
+//! The sender holds ids with values and the keys of an additively homomorphic
+//! scheme, and the receiver holds ids. Ids are blinded by commutative DH
+//! encryption in shuffled order, so that the receiver matches them without
+//! learning which ones are common, and sums the encrypted values of matches.
+//! Both parties learn only the size of the intersection and the sum.
+
+use super::{blind, decode, dedup, frame, shuffle, unframe};
+use crate::math::weierstrass::encode_points;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::gentry::AdditiveHE;
+use crate::protocol::party::TwoParty;
+use crate::store::StoreValue;
+use k256::{
+    elliptic_curve::{rand_core::OsRng, Field},
+    Scalar,
+};
+use std::collections::HashSet;
+
+/// size of the intersection and sum of the sender's values over it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Output {
+    pub cardinality: usize,
+    pub sum: u64,
+}
+
+fn malformed() -> Error {
+    Error::MPCError(MPCErrorKind::MalformedMessage)
+}
+
+/// holds ids with values and decrypts the sum
+pub struct Sender<H> {
+    party: TwoParty,
+    scheme: H,
+}
+
+impl<H: AdditiveHE> Sender<H>
+where
+    H::PublicKey: StoreValue,
+    H::Ciphertext: StoreValue,
+{
+    pub fn new(party: TwoParty, scheme: H) -> Self {
+        Self { party, scheme }
+    }
+
+    /// runs the protocol on pairs of id and value, whose ids are distinct
+    pub fn run(&self, pairs: &[(String, u64)]) -> Result<Output> {
+        let (secret, public) = self.scheme.generate();
+        let key = Scalar::random(&mut OsRng);
+        let mut theirs: Vec<_> = decode(&self.party.pull()?)?
+            .into_iter()
+            .map(|point| point * key)
+            .collect();
+        shuffle(&mut theirs);
+        let mut pairs = pairs.to_vec();
+        shuffle(&mut pairs);
+        let ids: Vec<String> = pairs.iter().map(|(id, _)| id.clone()).collect();
+        let ciphers: Vec<Vec<u8>> = pairs
+            .iter()
+            .map(|(_, value)| self.scheme.encrypt(&public, *value).to_bytes())
+            .collect();
+        self.party.push(&encode_points(&theirs))?;
+        self.party.push(&public.to_bytes())?;
+        self.party.push(&encode_points(&blind(&ids, &key)))?;
+        self.party.push(&frame(&ciphers))?;
+        let reply = self.party.pull()?;
+        let (cardinality, sum) = reply.split_first_chunk::<8>().ok_or_else(malformed)?;
+        let sum = H::Ciphertext::from_bytes(sum).map_err(|_| malformed())?;
+        let sum = self.scheme.decrypt(&secret, &sum).ok_or_else(malformed)?;
+        self.party.push(&sum.to_le_bytes())?;
+        Ok(Output {
+            cardinality: u64::from_le_bytes(*cardinality) as usize,
+            sum,
+        })
+    }
+}
+
+/// holds ids and sums the encrypted values of common ids
+pub struct Receiver<H> {
+    party: TwoParty,
+    scheme: H,
+}
+
+impl<H: AdditiveHE> Receiver<H>
+where
+    H::PublicKey: StoreValue,
+    H::Ciphertext: StoreValue,
+{
+    pub fn new(party: TwoParty, scheme: H) -> Self {
+        Self { party, scheme }
+    }
+
+    pub fn run(&self, ids: &[String]) -> Result<Output> {
+        let key = Scalar::random(&mut OsRng);
+        let mut blinded = blind(&dedup(ids), &key);
+        shuffle(&mut blinded);
+        self.party.push(&encode_points(&blinded))?;
+        let mine: HashSet<Vec<u8>> = decode(&self.party.pull()?)?
+            .iter()
+            .map(|point| encode_points(&[*point]))
+            .collect();
+        let public = H::PublicKey::from_bytes(&self.party.pull()?).map_err(|_| malformed())?;
+        let theirs = decode(&self.party.pull()?)?;
+        let ciphers = unframe(&self.party.pull()?)?;
+        if ciphers.len() != theirs.len() {
+            return Err(malformed());
+        }
+        let mut matched = vec![];
+        for (point, cipher) in theirs.iter().zip(ciphers) {
+            if mine.contains(&encode_points(&[*point * key])) {
+                matched.push(H::Ciphertext::from_bytes(&cipher).map_err(|_| malformed())?);
+            }
+        }
+        // the sum starts from a fresh encryption of zero, which rerandomizes it
+        let sum = self.scheme.sum(&public, &matched)?;
+        let cardinality = (matched.len() as u64).to_le_bytes();
+        self.party
+            .push(&[cardinality.as_slice(), &sum.to_bytes()].concat())?;
+        let sum = self.party.pull()?;
+        Ok(Output {
+            cardinality: matched.len(),
+            sum: u64::from_bytes(&sum).map_err(|_| malformed())?,
+        })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::protocol::gentry::paillier::Paillier;
+    use crate::store::{disk::DiskStore, Store};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    // the sender's spend per user and the receiver's users who saw an ad
+    let mut spend = DiskStore::new("data/Alice", "spend").unwrap();
+    let mut pairs = vec![];
+    for i in 0..30u64 {
+        let (id, value) = (format!("user{}", 2 * i), 100 + i * i);
+        spend.set(&id, &value).unwrap();
+        pairs.push((id.clone(), spend.get::<u64>(&id).unwrap()));
+    }
+    let mut viewers = DiskStore::new("data/Bob", "viewers").unwrap();
+    let keys: Vec<String> = (0..25).map(|i| format!("viewer{i}")).collect();
+    for (i, key) in keys.iter().enumerate() {
+        viewers.set(key, &format!("user{}", 3 * i)).unwrap();
+    }
+    let ids = super::from_store(&viewers, &keys).unwrap();
+    // common users are multiples of 6 below 60
+    let expected = Output {
+        cardinality: 10,
+        sum: (0..10u64).map(|i| 100 + 9 * i * i).sum(),
+    };
+
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9140 + i as u16)));
+    let scheme = Paillier::<2, 4>::new(128);
+    thread::scope(|scope| {
+        let pairs = &pairs;
+        let sender = scope.spawn(move || {
+            Sender::new(TwoParty::new(0, &peers).unwrap(), scheme)
+                .run(pairs)
+                .unwrap()
+        });
+        let ids = &ids;
+        let receiver = scope.spawn(move || {
+            Receiver::new(TwoParty::new(1, &peers).unwrap(), scheme)
+                .run(ids)
+                .unwrap()
+        });
+        assert_eq!(expected, sender.join().unwrap());
+        assert_eq!(expected, receiver.join().unwrap());
+    });
+}
@@ -0,0 +1,162 @@
+//! implements private set union from DH membership tests and OT
+//! Scalable Private Set Union from Symmetric-Key Techniques
+//! https://eprint.iacr.org/2019/776.pdf
+//! The receiver tests each of the sender's blinded items for membership in its
+//! own set, as in DH-based PSI, and chooses by OT the key of each item it lacks.
+//! The sender encrypts every item under its own key, so that the receiver
+//! decrypts exactly the items outside its set and learns only the union.
+
+use super::{blind, decode, dedup, frame, shuffle, unframe};
+use crate::math::weierstrass::encode_points;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::ot::{co15, Choice, OTReceive, OTSend};
+use crate::protocol::party::TwoParty;
+use blake2::{Blake2b512, Digest};
+use k256::{
+    elliptic_curve::{
+        rand_core::{OsRng, RngCore},
+        Field,
+    },
+    Scalar,
+};
+use std::collections::HashSet;
+
+/// encrypts or decrypts an item by XOR with a stream expanded from the key
+fn cipher(key: &[u8; 32], item: &[u8]) -> Vec<u8> {
+    let stream = (0u64..).flat_map(|counter| {
+        Blake2b512::new()
+            .chain_update(key)
+            .chain_update(counter.to_le_bytes())
+            .finalize()
+    });
+    item.iter().zip(stream).map(|(x, s)| x ^ s).collect()
+}
+
+fn malformed() -> Error {
+    Error::MPCError(MPCErrorKind::MalformedMessage)
+}
+
+/// holds a set and learns only the size of the receiver's set
+pub struct Sender {
+    party: TwoParty,
+}
+
+impl Sender {
+    pub fn new(party: TwoParty) -> Self {
+        Self { party }
+    }
+
+    pub fn run(&self, set: &[String]) -> Result<()> {
+        let key = Scalar::random(&mut OsRng);
+        let mut theirs: Vec<_> = decode(&self.party.pull()?)?
+            .into_iter()
+            .map(|point| point * key)
+            .collect();
+        shuffle(&mut theirs);
+        let mut set = dedup(set);
+        shuffle(&mut set);
+        let keys: Vec<[u8; 32]> = set
+            .iter()
+            .map(|_| {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                key
+            })
+            .collect();
+        let ciphers: Vec<Vec<u8>> = set
+            .iter()
+            .zip(&keys)
+            .map(|(item, key)| cipher(key, item.as_bytes()))
+            .collect();
+        self.party.push(&encode_points(&theirs))?;
+        self.party.push(&encode_points(&blind(&set, &key)))?;
+        self.party.push(&frame(&ciphers))?;
+        // the first message is useless to a receiver holding the item
+        let sender = co15::Sender(self.party.peer(1 - self.party.id));
+        for key in keys {
+            let mut useless = [0u8; 32];
+            OsRng.fill_bytes(&mut useless);
+            sender.send(&[useless, key])?;
+        }
+        Ok(())
+    }
+}
+
+/// holds a set and learns the union
+pub struct Receiver {
+    party: TwoParty,
+}
+
+impl Receiver {
+    pub fn new(party: TwoParty) -> Self {
+        Self { party }
+    }
+
+    /// computes the union, which lists the given set before the sender's other items
+    pub fn run(&self, set: &[String]) -> Result<Vec<String>> {
+        let mut set = dedup(set);
+        let key = Scalar::random(&mut OsRng);
+        self.party.push(&encode_points(&blind(&set, &key)))?;
+        let mine: HashSet<Vec<u8>> = decode(&self.party.pull()?)?
+            .iter()
+            .map(|point| encode_points(&[*point]))
+            .collect();
+        let theirs = decode(&self.party.pull()?)?;
+        let ciphers = unframe(&self.party.pull()?)?;
+        if ciphers.len() != theirs.len() {
+            return Err(malformed());
+        }
+        let receiver = co15::Receiver(self.party.peer(1 - self.party.id));
+        for (point, item) in theirs.iter().zip(ciphers) {
+            let missing = !mine.contains(&encode_points(&[*point * key]));
+            let key: [u8; 32] = receiver.receive(&Choice::<2>::new(missing as usize).unwrap())?;
+            if missing {
+                set.push(String::from_utf8(cipher(&key, &item)).map_err(|_| malformed())?);
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::store::{disk::DiskStore, Store};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    // records of both owners overlap in a third of their ids
+    let mut alice = DiskStore::new("data/Alice", "ids").unwrap();
+    let mut bob = DiskStore::new("data/Bob", "ids").unwrap();
+    let keys: Vec<String> = (0..30).map(|i| format!("record{i}")).collect();
+    for (i, key) in keys.iter().enumerate() {
+        alice.set(key, &format!("id-{i}")).unwrap();
+        bob.set(key, &format!("id-{}", i + 20)).unwrap();
+    }
+    let alice = super::from_store(&alice, &keys).unwrap();
+    let bob = super::from_store(&bob, &keys).unwrap();
+
+    let peers: [SocketAddr; 2] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9150 + i as u16)));
+    thread::scope(|scope| {
+        let bob = &bob;
+        scope.spawn(move || {
+            Sender::new(TwoParty::new(0, &peers).unwrap())
+                .run(bob)
+                .unwrap()
+        });
+        let alice = &alice;
+        let mut union = scope
+            .spawn(move || {
+                Receiver::new(TwoParty::new(1, &peers).unwrap())
+                    .run(alice)
+                    .unwrap()
+            })
+            .join()
+            .unwrap();
+        assert_eq!(alice[..], union[..30]);
+        union.sort();
+        let mut expected: Vec<String> = (0..50).map(|i| format!("id-{i}")).collect();
+        expected.sort();
+        assert_eq!(expected, union);
+    });
+}
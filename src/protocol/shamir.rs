@@ -12,4 +12,22 @@ pub trait SSServer<const T: usize, const N: usize, F: FiniteField> {
     fn recover(&self, shares: &[Option<F>; N]) -> Result<F>;
 }
 
+/// (T, N) - packed secret sharing server over field F, which embeds K secrets
+/// into one polynomial, so that the share of each party is a vector of length
+/// `ceil(secrets.len() / K)`
+pub trait PackedSSServer<const T: usize, const N: usize, const K: usize, F: FiniteField> {
+    fn split(&self, secrets: &[F]) -> Result<[Vec<F>; N]>;
+    fn recover(&self, shares: &[Option<Vec<F>>; N]) -> Result<Vec<F>>;
+}
+
+pub mod aggregate;
+pub mod bgw88;
+pub mod compare;
+pub mod feldman;
 pub mod functionality;
+pub mod packed;
+pub mod refresh;
+pub mod reshare;
+pub mod s79;
+pub mod shuffle;
+pub mod sort;
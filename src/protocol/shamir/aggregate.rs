@@ -0,0 +1,463 @@
+//! implements aggregate queries over secret-shared numeric columns
+//! Columns of integers are shared among the servers, which compute sums,
+//! counts, averages and histograms, optionally over the rows selected by a
+//! range, and open only the result. A signed value is embedded as its
+//! negation, and is offset by half of its range for comparisons. Results are
+//! read back as integers whose magnitude is less than `2^127`, so that the
+//! field must exceed twice any aggregate. Selections and histograms compare
+//! values masked by 40 extra bits, which requires a prime of more than
+//! `bits + 40` bits, e.g., a 62-bit prime compares columns of up to 16 bits.
+//! The average over a selection is read back as a fraction, which requires the
+//! magnitude of the column's sum and its length to be at most `sqrt(p / 2)`.
+//! Both fail with `FieldTooSmall` otherwise. A column under a privacy policy is
+//! protected, so that neither its shares nor the rows selected from it are
+//! opened, and its aggregates are released with noise by `protocol::dp`.
+
+use super::{bgw88::Evaluator, SSServer};
+use crate::math::galois::FiniteField;
+use crate::math::gauss::{NonZero, U512};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::store::{Store, StoreValue};
+use std::io::ErrorKind;
+
+/// integer types of a column
+pub trait Numeric: StoreValue + Copy {
+    const BITS: usize;
+    const SIGNED: bool;
+    /// returns the magnitude and whether the value is negative
+    fn split(self) -> (u128, bool);
+}
+
+macro_rules! impl_numeric_for_unsigned_type {
+    ($($t:ty),*) => {
+        $(impl Numeric for $t {
+            const BITS: usize = <$t>::BITS as usize;
+            const SIGNED: bool = false;
+            fn split(self) -> (u128, bool) {
+                (self as u128, false)
+            }
+        })*
+    };
+}
+
+macro_rules! impl_numeric_for_signed_type {
+    ($($t:ty),*) => {
+        $(impl Numeric for $t {
+            const BITS: usize = <$t>::BITS as usize;
+            const SIGNED: bool = true;
+            fn split(self) -> (u128, bool) {
+                (self.unsigned_abs() as u128, self < 0)
+            }
+        })*
+    };
+}
+
+impl_numeric_for_unsigned_type!(u8, u16, u32, u64, u128);
+impl_numeric_for_signed_type!(i8, i16, i32, i64, i128);
+
+/// shares of a column with the width and signedness of its type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column<F> {
+    pub(crate) shares: Vec<F>,
+    pub bits: usize,
+    pub signed: bool,
+    protected: bool,
+}
+
+/// shared bits of the rows selected from a column, which are consumed by
+/// aggregates only and keep the protection of the column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection<F> {
+    bits: Vec<F>,
+    protected: bool,
+}
+
+impl<F> Column<F> {
+    /// gets the shares, which fails if the column is protected
+    pub fn shares(&self) -> Result<&[F]> {
+        match self.protected {
+            true => Err(Error::MPCError(MPCErrorKind::PolicyViolation)),
+            false => Ok(&self.shares),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.shares.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shares.is_empty()
+    }
+
+    /// appends the rows of a column of the same type, e.g., of another owner,
+    /// which keeps the result protected if either column is
+    pub fn append(&mut self, other: Column<F>) {
+        assert_eq!((self.bits, self.signed), (other.bits, other.signed));
+        self.shares.extend(other.shares);
+        self.protected |= other.protected;
+    }
+
+    /// forbids opening exact aggregates of this column, which cannot be undone
+    pub fn protect(&mut self) {
+        self.protected = true;
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.protected
+    }
+}
+
+/// reads a column from the store, which fails if a key is missing
+pub fn read<V: Numeric>(store: &impl Store, keys: &[String]) -> Result<Vec<V>> {
+    keys.iter()
+        .map(|key| store.get::<V>(key))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| Error::IOError(ErrorKind::NotFound))
+}
+
+/// embeds an integer into the field
+pub(crate) fn embed<F: FiniteField, V: Numeric>(field: &F, value: V) -> F {
+    let (magnitude, negative) = value.split();
+    let shift = field.embed(1 << 32) * field.embed(1 << 32);
+    let x = field.embed((magnitude >> 64) as u64) * shift + field.embed(magnitude as u64);
+    if negative {
+        -x
+    } else {
+        x
+    }
+}
+
+/// reads the little-endian encoding of an element as an integer
+fn integer<F: FiniteField>(x: &F) -> U512 {
+    let bytes = x.encode();
+    assert!(bytes.len() <= 64, "the field must have at most 512 bits");
+    let mut padded = [0u8; 64];
+    padded[..bytes.len()].copy_from_slice(&bytes);
+    U512::from_le_slice(&padded)
+}
+
+/// converts an integer of at most 127 bits
+fn small(value: &U512) -> Option<i128> {
+    (value.bits() <= 127).then(|| {
+        let bytes = value.to_le_bytes();
+        i128::from_le_bytes(bytes[..16].try_into().unwrap())
+    })
+}
+
+/// reads the characteristic of a prime field as an integer
+fn modulus<F: FiniteField>(x: &F) -> U512 {
+    integer(&-x.one()).wrapping_add(&U512::ONE)
+}
+
+/// reads an element as the integer of least magnitude, which is negative
+/// for the representatives above `(p - 1) / 2`
+pub(crate) fn decode<F: FiniteField>(x: &F) -> Result<i128> {
+    let (p, value) = (modulus(x), integer(x));
+    let decoded = if value > p.wrapping_sub(&U512::ONE).shr_vartime(1) {
+        small(&p.wrapping_sub(&value)).map(|v| -v)
+    } else {
+        small(&value)
+    };
+    decoded.ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))
+}
+
+/// finds the fraction `a / b` equal to `q` in the field, where `|a|` and `b`
+/// are at most `sqrt(p / 2)`, by the extended Euclidean algorithm
+fn reconstruct<F: FiniteField>(q: &F) -> Result<(i128, i128)> {
+    let modulus = modulus(q);
+    let bound = modulus.shr_vartime(1).sqrt_vartime();
+    // the coefficients of q alternate in sign, so that only magnitudes are kept
+    let (mut r0, mut r1) = (modulus, integer(q));
+    let (mut t0, mut t1) = (U512::ZERO, U512::ONE);
+    let mut negative = false;
+    while r1 > bound {
+        let (quotient, remainder) = r0.div_rem(&NonZero::new(r1).unwrap());
+        (r0, r1) = (r1, remainder);
+        (t0, t1) = (t1, t0.wrapping_add(&quotient.wrapping_mul(&t1)));
+        negative = !negative;
+    }
+    let malformed = Error::MPCError(MPCErrorKind::MalformedMessage);
+    if t1 > bound {
+        return Err(malformed);
+    }
+    let (a, b) = (small(&r1).ok_or(malformed)?, small(&t1).unwrap());
+    Ok(if negative { (-a, b) } else { (a, b) })
+}
+
+impl<const T: usize, const N: usize, F, S> Evaluator<T, N, F, S>
+where
+    F: FiniteField,
+    S: SSServer<T, N, F>,
+{
+    /// shares the column of party `owner`, where other parties pass no values
+    pub fn input_column<V: Numeric>(&self, owner: usize, values: &[V]) -> Result<Column<F>> {
+        let field = *self.field();
+        let values: Vec<F> = values.iter().map(|v| embed(&field, *v)).collect();
+        Ok(Column {
+            shares: self.input(owner, &values)?,
+            bits: V::BITS,
+            signed: V::SIGNED,
+            protected: false,
+        })
+    }
+
+    /// fails unless any sum and count of the column are at most `sqrt(p / 2)`,
+    /// so that their ratio is read back as a fraction
+    fn fraction(&self, column: &Column<F>) -> Result<()> {
+        let bound = modulus(self.field()).shr_vartime(1).sqrt_vartime();
+        let magnitude = (column.bits - column.signed as usize) as u32;
+        match U512::from_u64(column.len() as u64).shl_vartime(magnitude) > bound {
+            true => Err(Error::MPCError(MPCErrorKind::FieldTooSmall)),
+            false => Ok(()),
+        }
+    }
+
+    /// fails if the column or the selection is protected by a privacy policy
+    fn exact(&self, column: &Column<F>, selection: Option<&Selection<F>>) -> Result<()> {
+        match column.protected || selection.is_some_and(|s| s.protected) {
+            true => Err(Error::MPCError(MPCErrorKind::PolicyViolation)),
+            false => Ok(()),
+        }
+    }
+
+    /// computes shared bits of `x < bound` for each bound and row
+    fn below<V: Numeric>(&self, column: &Column<F>, bounds: &[V]) -> Result<Vec<Vec<F>>> {
+        assert_eq!(
+            (column.bits, column.signed),
+            (V::BITS, V::SIGNED),
+            "bounds must have the type of the column"
+        );
+        if column.is_empty() {
+            return Ok(vec![vec![]; bounds.len()]);
+        }
+        let field = *self.field();
+        let offset = match column.signed {
+            true => (1..column.bits).fold(field.one(), |acc, _| acc + acc),
+            false => field.zero(),
+        };
+        let xs: Vec<F> = column.shares.iter().map(|x| *x + offset).collect();
+        let (xs, ys): (Vec<F>, Vec<F>) = bounds
+            .iter()
+            .flat_map(|bound| {
+                let y = embed(&field, *bound) + offset;
+                xs.iter().map(move |x| (*x, y))
+            })
+            .unzip();
+        let lt = self.less_than(&xs, &ys, column.bits)?;
+        Ok(lt.chunks(column.len()).map(<[F]>::to_vec).collect())
+    }
+
+    /// selects the rows in `[low, high)` as shared bits, which fails with
+    /// `FieldTooSmall` unless the prime has more than `bits + 40` bits
+    pub fn select<V: Numeric>(&self, column: &Column<F>, low: V, high: V) -> Result<Selection<F>> {
+        let lt = self.below(column, &[low, high])?;
+        Ok(Selection {
+            bits: lt[1].iter().zip(&lt[0]).map(|(h, l)| *h - l).collect(),
+            protected: column.protected,
+        })
+    }
+
+    /// shares the sum of the column, or of the selected rows
+    pub(crate) fn total(&self, column: &Column<F>, selection: Option<&Selection<F>>) -> Result<F> {
+        let values = match selection {
+            Some(selection) if selection.bits.len() != column.len() => {
+                return Err(Error::IOError(ErrorKind::InvalidInput))
+            }
+            Some(selection) => self.mul(&column.shares, &selection.bits)?,
+            None => column.shares.clone(),
+        };
+        Ok(values.iter().fold(self.field().zero(), |acc, x| acc + x))
+    }
+
+    /// shares the number of rows of the column, or of the selected rows
+    pub(crate) fn tally(&self, column: &Column<F>, selection: Option<&Selection<F>>) -> F {
+        let field = *self.field();
+        match selection {
+            Some(selection) => selection.bits.iter().fold(field.zero(), |acc, x| acc + x),
+            None => field.embed(column.len() as u64),
+        }
+    }
+
+    /// shares the number of rows in each bucket `[boundaries[i], boundaries[i + 1])`
+    pub(crate) fn buckets<V: Numeric>(
+        &self,
+        column: &Column<F>,
+        boundaries: &[V],
+    ) -> Result<Vec<F>> {
+        let lt = self.below(column, boundaries)?;
+        let zero = self.field().zero();
+        Ok(lt
+            .windows(2)
+            .map(|pair| {
+                pair[1]
+                    .iter()
+                    .zip(&pair[0])
+                    .fold(zero, |acc, (h, l)| acc + h - l)
+            })
+            .collect())
+    }
+
+    /// opens the sum of the column, or of the selected rows
+    pub fn sum(&self, column: &Column<F>, selection: Option<&Selection<F>>) -> Result<i128> {
+        self.exact(column, selection)?;
+        decode(&self.open(&[self.total(column, selection)?])?[0])
+    }
+
+    /// opens the number of selected rows of the column
+    pub fn count(&self, column: &Column<F>, selection: &Selection<F>) -> Result<u64> {
+        self.exact(column, Some(selection))?;
+        let count = self.tally(column, Some(selection));
+        Ok(decode(&self.open(&[count])?[0])? as u64)
+    }
+
+    /// opens the average of the column, or of the selected rows, which is None
+    /// if there are none. As the number of selected rows is secret, the sum and
+    /// the count are multiplied by a shared random value, so that only their
+    /// ratio is opened and read back as a fraction, which fails with
+    /// `FieldTooSmall` if the column may exceed the bound of the module.
+    pub fn average(
+        &self,
+        column: &Column<F>,
+        selection: Option<&Selection<F>>,
+    ) -> Result<Option<f64>> {
+        self.exact(column, selection)?;
+        let Some(selection) = selection else {
+            let sum = self.sum(column, None)?;
+            return Ok((!column.is_empty()).then(|| sum as f64 / column.len() as f64));
+        };
+        self.fraction(column)?;
+        let zero = self.field().zero();
+        let sum = self.total(column, Some(selection))?;
+        let count = self.tally(column, Some(selection));
+        let mask = self.random(1)?[0];
+        let opened = self.open(&self.mul(&[sum, count], &[mask, mask])?)?;
+        if opened[1] == zero {
+            return Ok(None);
+        }
+        let (a, b) = reconstruct(&(opened[0] * opened[1].inv()))?;
+        Ok(Some(a as f64 / b as f64))
+    }
+
+    /// opens the number of rows in each bucket `[boundaries[i], boundaries[i + 1])`,
+    /// which requires a field as large as `select` does
+    pub fn histogram<V: Numeric>(&self, column: &Column<F>, boundaries: &[V]) -> Result<Vec<u64>> {
+        self.exact(column, None)?;
+        let counts = self.buckets(column, boundaries)?;
+        self.open(&counts)?
+            .iter()
+            .map(|count| Ok(decode(count)? as u64))
+            .collect()
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::s79::Server;
+    use crate::math::PrimeModulus;
+    use crate::protocol::party::MultiParty;
+    use crate::store::disk::DiskStore;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let alice: Vec<i8> = vec![12, -5, 23, 0, 17, 31];
+    let bob: Vec<i8> = vec![8, 19, -120, -97];
+    let keys = |len: usize| -> Vec<String> { (0..len).map(|i| format!("row:{i}")).collect() };
+    let mut stores = vec![];
+    for (name, column) in [("data/Alice", &alice), ("data/Bob", &bob)] {
+        let mut store = DiskStore::new(name, "temperatures").unwrap();
+        for (key, value) in keys(column.len()).iter().zip(column) {
+            store.set(key, value).unwrap();
+        }
+        stores.push(store);
+    }
+    assert!(read::<i8>(&stores[0], &keys(7)).is_err());
+
+    let field = PrimeModulus::<1>::from_random(62).random_make();
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9200 + i as u16)));
+    let boundaries = [i8::MIN, 0, 10, 20, i8::MAX];
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                let (stores, keys) = (&stores, &keys);
+                let (alice, bob) = (alice.len(), bob.len());
+                scope.spawn(move || {
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    let evaluator = Evaluator::new(party, Server::<2, 3>, field);
+                    let own = |owner: usize, len: usize| match owner == id {
+                        true => read::<i8>(&stores[owner], &keys(len)).unwrap(),
+                        false => vec![],
+                    };
+                    let mut column = evaluator.input_column(0, &own(0, alice)).unwrap();
+                    column.append(evaluator.input_column(1, &own(1, bob)).unwrap());
+                    let selection = evaluator.select(&column, 10i8, 20).unwrap();
+                    let empty = Selection {
+                        bits: vec![field.zero(); column.len()],
+                        protected: false,
+                    };
+                    // u32 values are too wide to compare or average in a 62-bit field
+                    let wide = match id {
+                        0 => vec![70000u32; column.len()],
+                        _ => vec![],
+                    };
+                    let wide = evaluator.input_column(0, &wide).unwrap();
+                    let small = Error::MPCError(MPCErrorKind::FieldTooSmall);
+                    assert_eq!(Err(small), evaluator.select(&wide, 0u32, 10));
+                    assert_eq!(Err(small), evaluator.histogram(&wide, &[0u32, 10]));
+                    assert_eq!(Err(small), evaluator.average(&wide, Some(&selection)));
+                    assert_eq!(
+                        70000 * column.len() as i128,
+                        evaluator.sum(&wide, None).unwrap()
+                    );
+                    let result = (
+                        evaluator.sum(&column, None).unwrap(),
+                        evaluator.sum(&column, Some(&selection)).unwrap(),
+                        evaluator.count(&column, &selection).unwrap(),
+                        evaluator.average(&column, None).unwrap(),
+                        evaluator.average(&column, Some(&selection)).unwrap(),
+                        evaluator.average(&column, Some(&empty)).unwrap(),
+                        evaluator.histogram(&column, &boundaries).unwrap(),
+                    );
+                    // a protected column is neither opened nor selected from directly
+                    column.protect();
+                    let violation = Error::MPCError(MPCErrorKind::PolicyViolation);
+                    assert_eq!(Err(violation), column.shares());
+                    let selection = evaluator.select(&column, 10i8, 20).unwrap();
+                    assert_eq!(Err(violation), evaluator.count(&column, &selection));
+                    // nor through another column of the same rows
+                    let unprotected = evaluator.input_column(0, &own(0, alice)).unwrap();
+                    assert_eq!(
+                        Err(violation),
+                        evaluator.sum(&unprotected, Some(&selection))
+                    );
+                    result
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let all: Vec<i128> = alice.iter().chain(&bob).map(|&v| v as i128).collect();
+    let selected: Vec<i128> = all
+        .iter()
+        .copied()
+        .filter(|v| (10..20).contains(v))
+        .collect();
+    let sum: i128 = all.iter().sum();
+    // negative results are read back from the upper half of the field
+    assert!(sum < 0);
+    assert_eq!(-1395, decode(&-field.embed(1395)).unwrap());
+    assert_eq!(1395, decode(&field.embed(1395)).unwrap());
+    let selected_sum: i128 = selected.iter().sum();
+    let expected = (
+        sum,
+        selected_sum,
+        selected.len() as u64,
+        Some(sum as f64 / all.len() as f64),
+        Some(selected_sum as f64 / selected.len() as f64),
+        None,
+        vec![3, 2, 3, 2],
+    );
+    for result in results {
+        assert_eq!(expected, result);
+    }
+}
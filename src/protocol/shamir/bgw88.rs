@@ -0,0 +1,198 @@
+//! implements arithmetic on secret shares with honest majority
+//! Completeness Theorems for Non-Cryptographic Fault-Tolerant Distributed Computation
+//! https://dl.acm.org/doi/10.1145/62212.62213
+//! Shares are added locally, while a multiplication reduces the degree of the
+//! local products by resharing them, which requires 2T - 1 <= N.
+
+use super::SSServer;
+use crate::math::galois::{decode_all, encode_all, FiniteField};
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::MultiParty;
+
+/// computes on (T, N) shares over the field of `field` among N parties
+/// All operations are batched, so each of them costs one round regardless of the batch size.
+pub struct Evaluator<const T: usize, const N: usize, F, S> {
+    party: MultiParty<N>,
+    server: S,
+    field: F,
+}
+
+impl<const T: usize, const N: usize, F, S> Evaluator<T, N, F, S>
+where
+    F: FiniteField,
+    S: SSServer<T, N, F>,
+{
+    /// creates an evaluator, where `field` is any element of the field
+    pub fn new(party: MultiParty<N>, server: S, field: F) -> Self {
+        assert!(2 * T <= N + 1, "multiplication requires 2T - 1 <= N");
+        Self {
+            party,
+            server,
+            field,
+        }
+    }
+
+    /// id of this party
+    pub fn id(&self) -> usize {
+        self.party.id
+    }
+
+    /// any element of the field to compute on
+    pub fn field(&self) -> &F {
+        &self.field
+    }
+
+    /// party to talk to a subset of the parties directly
+    pub(super) fn party(&self) -> &MultiParty<N> {
+        &self.party
+    }
+
+    /// sends a vector to every other party and receives theirs, where the
+    /// message for party `i` is at index `i` and that from party `i` is returned at index `i`
+    fn exchange(&self, msgs: Vec<Vec<F>>) -> Result<Vec<Vec<F>>> {
+        let me = self.party.id;
+        for (id, msg) in msgs.iter().enumerate().filter(|&(id, _)| id != me) {
+            self.party.send(&id, &encode_all(msg))?;
+        }
+        msgs.into_iter()
+            .enumerate()
+            .map(|(id, msg)| {
+                if id == me {
+                    Ok(msg)
+                } else {
+                    decode_all(&self.field, &self.party.recv(&id)?)
+                        .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))
+                }
+            })
+            .collect()
+    }
+
+    /// splits every value and transposes the shares into a vector per party
+    fn split_all(&self, values: &[F]) -> Result<Vec<Vec<F>>> {
+        let mut msgs = vec![Vec::with_capacity(values.len()); N];
+        for value in values {
+            for (msg, share) in msgs.iter_mut().zip(self.server.split(*value)?) {
+                msg.push(share);
+            }
+        }
+        Ok(msgs)
+    }
+
+    /// recovers every value from the vectors of shares of all parties
+    fn recover_all(&self, shares: &[Vec<F>]) -> Result<Vec<F>> {
+        let len = shares[0].len();
+        if shares.iter().any(|share| share.len() != len) {
+            return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+        }
+        (0..len)
+            .map(|j| {
+                self.server
+                    .recover(&std::array::from_fn(|i| Some(shares[i][j])))
+            })
+            .collect()
+    }
+
+    /// shares `values` of party `owner` to all parties, where other parties pass no values
+    pub fn input(&self, owner: usize, values: &[F]) -> Result<Vec<F>> {
+        if owner == self.party.id {
+            let mut msgs = self.split_all(values)?;
+            for (id, msg) in msgs.iter().enumerate().filter(|&(id, _)| id != owner) {
+                self.party.send(&id, &encode_all(msg))?;
+            }
+            Ok(msgs.swap_remove(owner))
+        } else {
+            decode_all(&self.field, &self.party.recv(&owner)?)
+                .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))
+        }
+    }
+
+    /// reveals the shared values to all parties
+    pub fn open(&self, shares: &[F]) -> Result<Vec<F>> {
+        let shares = self.exchange(vec![shares.to_vec(); N])?;
+        self.recover_all(&shares)
+    }
+
+    /// multiplies shares pairwise
+    pub fn mul(&self, xs: &[F], ys: &[F]) -> Result<Vec<F>> {
+        assert_eq!(xs.len(), ys.len());
+        let products: Vec<F> = xs.iter().zip(ys).map(|(x, y)| *x * y).collect();
+        let subshares = self.exchange(self.split_all(&products)?)?;
+        self.recover_all(&subshares)
+    }
+
+    /// samples shares of uniformly random values
+    pub fn random(&self, count: usize) -> Result<Vec<F>> {
+        let values: Vec<F> = (0..count).map(|_| self.field.random()).collect();
+        let sums = self.exchange(self.split_all(&values)?)?;
+        Ok((0..count)
+            .map(|j| sums.iter().fold(self.field.zero(), |acc, v| acc + v[j]))
+            .collect())
+    }
+
+    /// computes the xor of shared bits pairwise, which is `x + y - 2xy`
+    pub fn xor(&self, xs: &[F], ys: &[F]) -> Result<Vec<F>> {
+        let sums = xs.iter().zip(ys).map(|(x, y)| *x + y);
+        let two = self.field.one() + self.field.one();
+        if two == self.field.zero() {
+            return Ok(sums.collect());
+        }
+        let products = self.mul(xs, ys)?;
+        Ok(sums
+            .zip(products)
+            .map(|(sum, product)| sum - two * product)
+            .collect())
+    }
+
+    /// samples shares of uniformly random bits, as the xor of random bits of every party
+    pub fn random_bits(&self, count: usize) -> Result<Vec<F>> {
+        let bits: Vec<F> = (0..count)
+            .map(|_| self.field.embed(OsRng.next_u32() as u64 & 1))
+            .collect();
+        let inputs = self.exchange(self.split_all(&bits)?)?;
+        inputs[1..]
+            .iter()
+            .try_fold(inputs[0].clone(), |acc, bits| self.xor(&acc, bits))
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::s79::Server;
+    use crate::math::{abel::AbelianMonoid, PrimeModulus};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9050 + i as u16)));
+    let field = PrimeModulus::<1>::from_random(61).random_make();
+    let xs: Vec<_> = (0..4).map(|_| field.random()).collect();
+    let ys: Vec<_> = (0..4).map(|_| field.random()).collect();
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                let (xs, ys) = (&xs, &ys);
+                scope.spawn(move || {
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    let evaluator = Evaluator::new(party, Server::<2, 3>, field);
+                    let xs = evaluator.input(0, if id == 0 { xs } else { &[] }).unwrap();
+                    let ys = evaluator.input(1, if id == 1 { ys } else { &[] }).unwrap();
+                    let products = evaluator.mul(&xs, &ys).unwrap();
+                    let bits = evaluator.random_bits(16).unwrap();
+                    (
+                        evaluator.open(&products).unwrap(),
+                        evaluator.open(&bits).unwrap(),
+                    )
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let expected: Vec<_> = xs.iter().zip(&ys).map(|(x, y)| *x * y).collect();
+    for (products, bits) in results {
+        assert_eq!(expected, products);
+        assert!(bits
+            .iter()
+            .all(|bit| *bit == field.zero() || *bit == field.one()));
+    }
+}
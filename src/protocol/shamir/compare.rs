@@ -0,0 +1,203 @@
+//! implements bit decomposition, equality test and comparison of shared values
+//! Unconditionally Secure Constant-Rounds Multi-Party Computation for Equality, Comparison, Bits and Exponentiation
+//! https://link.springer.com/chapter/10.1007/11681878_15
+//! A shared value is masked by preprocessed random bits and opened, and its
+//! bits are recovered by a borrow chain. Outputs are shared bits, i.e., shares
+//! of zero or one, that can feed into further arithmetic.
+
+use super::{bgw88::Evaluator, SSServer};
+use crate::math::galois::FiniteField;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+
+/// statistical security parameter of masking values in a prime field
+const STATISTICAL: usize = 40;
+
+/// gets bit `i` of a little-endian encoding
+fn bit(bytes: &[u8], i: usize) -> bool {
+    bytes
+        .get(i / 8)
+        .is_some_and(|byte| byte >> (i % 8) & 1 == 1)
+}
+
+/// number of bits of a little-endian encoding
+fn bit_length(bytes: &[u8]) -> usize {
+    (0..bytes.len() * 8)
+        .rev()
+        .find(|&i| bit(bytes, i))
+        .map_or(0, |i| i + 1)
+}
+
+impl<const T: usize, const N: usize, F, S> Evaluator<T, N, F, S>
+where
+    F: FiniteField,
+    S: SSServer<T, N, F>,
+{
+    /// decomposes shared values into shared bits, least significant first
+    /// Values must lie in `[0, 2^width)`, where a value of a binary field is
+    /// read as the integer of its polynomial coefficients.
+    /// A prime field must have more than `width + 40` bits to mask the values.
+    pub fn bits(&self, xs: &[F], width: usize) -> Result<Vec<Vec<F>>> {
+        let field = *self.field();
+        let (zero, one) = (field.zero(), field.one());
+        let binary = one + one == zero;
+        let masks = if binary { width } else { width + STATISTICAL };
+        if !binary && masks >= bit_length(&(-one).encode()) {
+            return Err(Error::MPCError(MPCErrorKind::FieldTooSmall));
+        }
+        // mask values with random bits and open them
+        let random = self.random_bits(xs.len() * masks)?;
+        let masked: Vec<F> = xs
+            .iter()
+            .zip(random.chunks(masks))
+            .map(|(x, bits)| {
+                let two = field.embed(2);
+                *x + bits.iter().rev().fold(zero, |acc, bit| acc * two + bit)
+            })
+            .collect();
+        let opened: Vec<Vec<u8>> = self.open(&masked)?.iter().map(F::encode).collect();
+        if binary {
+            return Ok(opened
+                .iter()
+                .zip(random.chunks(masks))
+                .map(|(c, bits)| {
+                    (0..width)
+                        .map(|i| if bit(c, i) { bits[i] + one } else { bits[i] })
+                        .collect()
+                })
+                .collect());
+        }
+        // subtract the mask from the opened value bit by bit
+        let mut borrows = vec![zero; xs.len()];
+        let mut result = vec![Vec::with_capacity(width); xs.len()];
+        for i in 0..width {
+            let masks: Vec<F> = random.chunks(masks).map(|bits| bits[i]).collect();
+            let products = self.mul(&masks, &borrows)?;
+            for (j, c) in opened.iter().enumerate() {
+                let (r, b, m) = (masks[j], borrows[j], products[j]);
+                let xor = r + b - m - m;
+                if bit(c, i) {
+                    result[j].push(one - xor);
+                    borrows[j] = m;
+                } else {
+                    result[j].push(xor);
+                    borrows[j] = r + b - m;
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// computes the xor of the bits of `xs` and `ys` of given width
+    fn xor_bits(&self, xs: &[F], ys: &[F], width: usize) -> Result<(Vec<F>, Vec<F>)> {
+        let bits = self.bits(&[xs, ys].concat(), width)?;
+        let (xbits, ybits) = bits.split_at(xs.len());
+        let xbits: Vec<F> = xbits.concat();
+        let ybits: Vec<F> = ybits.concat();
+        Ok((self.xor(&xbits, &ybits)?, ybits))
+    }
+
+    /// tests `x == y` pairwise for shared values in `[0, 2^width)`
+    pub fn equal(&self, xs: &[F], ys: &[F], width: usize) -> Result<Vec<F>> {
+        let one = self.field().one();
+        let (diffs, _) = self.xor_bits(xs, ys, width)?;
+        let mut rows: Vec<Vec<F>> = diffs
+            .chunks(width.max(1))
+            .map(|row| row.iter().map(|d| one - d).collect())
+            .collect();
+        rows.resize(xs.len(), vec![]);
+        // multiply the bits of each row in a tree
+        while rows.iter().any(|row| row.len() > 1) {
+            let (lefts, rights): (Vec<F>, Vec<F>) = rows
+                .iter()
+                .flat_map(|row| row.chunks_exact(2).map(|pair| (pair[0], pair[1])))
+                .unzip();
+            let mut products = self.mul(&lefts, &rights)?.into_iter();
+            for row in rows.iter_mut() {
+                let odd = (row.len() % 2 == 1).then(|| row[row.len() - 1]);
+                *row = products.by_ref().take(row.len() / 2).chain(odd).collect();
+            }
+        }
+        Ok(rows
+            .into_iter()
+            .map(|row| row.first().copied().unwrap_or(one))
+            .collect())
+    }
+
+    /// tests `x < y` pairwise for shared values in `[0, 2^width)`
+    pub fn less_than(&self, xs: &[F], ys: &[F], width: usize) -> Result<Vec<F>> {
+        let one = self.field().one();
+        let (diffs, ybits) = self.xor_bits(xs, ys, width)?;
+        // from the least significant bit, the result is kept where the bits agree
+        // and set to the bit of y where they differ
+        let mut lt = vec![self.field().zero(); xs.len()];
+        for i in 0..width {
+            let at = |bits: &[F], j: usize| bits[j * width + i];
+            let agrees: Vec<F> = (0..xs.len()).map(|j| one - at(&diffs, j)).collect();
+            let deltas: Vec<F> = (0..xs.len()).map(|j| lt[j] - at(&ybits, j)).collect();
+            let products = self.mul(&agrees, &deltas)?;
+            for (j, product) in products.into_iter().enumerate() {
+                lt[j] = at(&ybits, j) + product;
+            }
+        }
+        Ok(lt)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::s79::Server;
+    use crate::math::{BinaryField, PrimeModulus};
+    use crate::protocol::party::MultiParty;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    fn check<F: FiniteField>(field: F, port: u16) {
+        let peers: [SocketAddr; 3] =
+            std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], port + i as u16)));
+        let xs = [0u64, 7, 200, 255, 13, 128];
+        let ys = [0u64, 9, 200, 254, 200, 127];
+        let results: Vec<_> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..3)
+                .map(|id| {
+                    scope.spawn(move || {
+                        let party = MultiParty::new(id, &peers).unwrap();
+                        let evaluator = Evaluator::new(party, Server::<2, 3>, field);
+                        let embed =
+                            |vs: &[u64]| -> Vec<F> { vs.iter().map(|&v| field.embed(v)).collect() };
+                        let xs = evaluator.input(0, &embed(&xs)).unwrap();
+                        let ys = evaluator.input(0, &embed(&ys)).unwrap();
+                        let bits = evaluator.bits(&xs, 8).unwrap();
+                        if field.one() + field.one() != field.zero() {
+                            // a 62-bit prime cannot mask 22-bit values with 40 extra bits
+                            assert_eq!(
+                                Error::MPCError(MPCErrorKind::FieldTooSmall),
+                                evaluator.bits(&xs, 22).unwrap_err()
+                            );
+                        }
+                        let eq = evaluator.equal(&xs, &ys, 8).unwrap();
+                        let lt = evaluator.less_than(&xs, &ys, 8).unwrap();
+                        (
+                            evaluator.open(&bits.concat()).unwrap(),
+                            evaluator.open(&eq).unwrap(),
+                            evaluator.open(&lt).unwrap(),
+                        )
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let bit = |b: bool| field.embed(b as u64);
+        let bits: Vec<F> = xs
+            .iter()
+            .flat_map(|x| (0..8).map(move |i| bit(x >> i & 1 == 1)))
+            .collect();
+        let eq: Vec<F> = xs.iter().zip(&ys).map(|(x, y)| bit(x == y)).collect();
+        let lt: Vec<F> = xs.iter().zip(&ys).map(|(x, y)| bit(x < y)).collect();
+        for result in results {
+            assert_eq!((bits.clone(), eq.clone(), lt.clone()), result);
+        }
+    }
+
+    check(PrimeModulus::<1>::from_random(62).random_make(), 9060);
+    check(BinaryField::<16, 1>::random_new(), 9070);
+}
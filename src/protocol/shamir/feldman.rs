@@ -0,0 +1,68 @@
+//! implements Feldman verifiable secret sharing over secp256k1
+//! A Practical Scheme for Non-interactive Verifiable Secret Sharing
+//! https://ieeexplore.ieee.org/document/4568297
+
+use super::SSServer;
+use crate::math::galois::FiniteField;
+use crate::math::lagrange;
+use crate::protocol::error::Result;
+use k256::{elliptic_curve::ops::MulByGenerator, ProjectivePoint, Scalar};
+
+/// shares a scalar like Shamir secret sharing, and commits to the polynomial
+/// coefficients in the exponent so that every party can verify its share
+pub struct Dealer<const T: usize, const N: usize>;
+
+impl<const T: usize, const N: usize> Dealer<T, N> {
+    /// splits `secret` into shares and commitments to the coefficients
+    pub fn deal(&self, secret: Scalar) -> ([Scalar; N], [ProjectivePoint; T]) {
+        let coeffs = lagrange::random(secret, T - 1);
+        let shares =
+            std::array::from_fn(|i| lagrange::evaluate(&coeffs, &secret.embed(i as u64 + 1)));
+        let commitments = std::array::from_fn(|k| ProjectivePoint::mul_by_generator(&coeffs[k]));
+        (shares, commitments)
+    }
+}
+
+impl<const T: usize, const N: usize> SSServer<T, N, Scalar> for Dealer<T, N> {
+    fn split(&self, secret: Scalar) -> Result<[Scalar; N]> {
+        Ok(self.deal(secret).0)
+    }
+
+    fn recover(&self, shares: &[Option<Scalar>; N]) -> Result<Scalar> {
+        super::s79::Server::<T, N>.recover(shares)
+    }
+}
+
+/// evaluates the committed polynomial in the exponent at party `id`,
+/// which is `share * G` for the share of that party
+pub fn public_share(commitments: &[ProjectivePoint], id: usize) -> ProjectivePoint {
+    let x = Scalar::from(id as u64 + 1);
+    commitments
+        .iter()
+        .rev()
+        .fold(ProjectivePoint::IDENTITY, |acc, commitment| {
+            acc * x + commitment
+        })
+}
+
+/// verifies the share of party `id` against the commitments
+pub fn verify(commitments: &[ProjectivePoint], id: usize, share: &Scalar) -> bool {
+    public_share(commitments, id) == ProjectivePoint::mul_by_generator(share)
+}
+
+#[test]
+fn test_correctness() {
+    let dealer = Dealer::<3, 5>;
+    let secret = Scalar::from(42u64);
+    let (shares, commitments) = dealer.deal(secret);
+    assert!((0..5).all(|id| verify(&commitments, id, &shares[id])));
+    assert!(!verify(&commitments, 0, &shares[1]));
+    let shares = [
+        None,
+        Some(shares[1]),
+        None,
+        Some(shares[3]),
+        Some(shares[4]),
+    ];
+    assert_eq!(secret, dealer.recover(&shares).unwrap());
+}
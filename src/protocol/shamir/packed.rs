@@ -0,0 +1,134 @@
+//! implements packed secret sharing algorithm
+//! Communication Complexity of Secure Computation
+//! https://dl.acm.org/doi/10.1145/129712.129780
+
+use super::PackedSSServer;
+use crate::math::galois::FiniteField;
+use crate::math::lagrange;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+
+/// shares K secrets as evaluations of a random polynomial of degree T - 1,
+/// where the secrets sit at `N + 1, ..., N + K` and the share of party `i` is
+/// evaluated at `i + 1`. Any T shares recover the secrets, while any T - K
+/// shares reveal nothing about them.
+/// The last polynomial is padded with zeros, so `recover` returns a multiple of K secrets.
+/// Splitting fails unless `0 < K <= T <= N`.
+/// # Example
+/// ```
+/// use dense::math::{galois, BinaryField};
+/// use dense::protocol::shamir::{packed::Server, PackedSSServer};
+/// use dense::store::{disk::DiskStore, Store};
+///
+/// let column: Vec<_> = (0..10).map(|_| BinaryField::<16, 1>::random_new()).collect();
+/// let shares = Server::<4, 6, 2>.split(&column).unwrap();
+/// let mut store = DiskStore::new("data/Alice", "shares").unwrap();
+/// store.set("column", &galois::encode_all(&shares[0])).unwrap();
+/// ```
+pub struct Server<const T: usize, const N: usize, const K: usize>;
+
+impl<const T: usize, const N: usize, const K: usize> Server<T, N, K> {
+    /// evaluation points of the secrets
+    fn secret_points<F: FiniteField>(field: &F) -> Vec<F> {
+        (0..K).map(|k| field.embed((N + 1 + k) as u64)).collect()
+    }
+}
+
+impl<const T: usize, const N: usize, const K: usize, F: FiniteField> PackedSSServer<T, N, K, F>
+    for Server<T, N, K>
+{
+    fn split(&self, secrets: &[F]) -> Result<[Vec<F>; N]> {
+        if K == 0 || K > T || T > N {
+            return Err(Error::MPCError(MPCErrorKind::InsufficientShares));
+        }
+        let mut shares = std::array::from_fn(|_| Vec::with_capacity(secrets.len().div_ceil(K)));
+        let Some(field) = secrets.first() else {
+            return Ok(shares);
+        };
+        // the polynomial is fixed by the secrets and T - K random shares
+        let mut xs = Self::secret_points(field);
+        xs.extend((0..T - K).map(|i| field.embed(i as u64 + 1)));
+        let bases: Vec<_> = (T - K..N)
+            .map(|i| lagrange::basis(&xs, &field.embed(i as u64 + 1)))
+            .collect();
+        for chunk in secrets.chunks(K) {
+            let mut ys = chunk.to_vec();
+            ys.resize(K, field.zero());
+            ys.extend((0..T - K).map(|_| field.random()));
+            for (share, y) in shares.iter_mut().zip(&ys[K..]) {
+                share.push(*y);
+            }
+            for (share, basis) in shares[T - K..].iter_mut().zip(&bases) {
+                share.push(
+                    basis
+                        .iter()
+                        .zip(&ys)
+                        .fold(field.zero(), |acc, (l, y)| acc + *l * y),
+                );
+            }
+        }
+        Ok(shares)
+    }
+
+    fn recover(&self, shares: &[Option<Vec<F>>; N]) -> Result<Vec<F>> {
+        let (xs, ys): (Vec<usize>, Vec<&Vec<F>>) = shares
+            .iter()
+            .enumerate()
+            .filter_map(|(i, share)| share.as_ref().map(|share| (i, share)))
+            .unzip();
+        if xs.len() < T || xs.is_empty() {
+            return Err(Error::MPCError(MPCErrorKind::InsufficientShares));
+        }
+        let len = ys[0].len();
+        if ys.iter().any(|share| share.len() != len) {
+            return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+        }
+        let Some(field) = ys[0].first() else {
+            return Ok(vec![]);
+        };
+        let xs: Vec<_> = xs.iter().map(|&i| field.embed(i as u64 + 1)).collect();
+        let bases: Vec<_> = Self::secret_points(field)
+            .iter()
+            .map(|at| lagrange::basis(&xs, at))
+            .collect();
+        let mut secrets = Vec::with_capacity(len * K);
+        for j in 0..len {
+            secrets.extend(bases.iter().map(|basis| {
+                basis
+                    .iter()
+                    .zip(&ys)
+                    .fold(field.zero(), |acc, (l, share)| acc + *l * share[j])
+            }));
+        }
+        Ok(secrets)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::math::{BinaryField, PrimeModulus};
+
+    fn check<F: FiniteField>(field: F) {
+        let server = Server::<5, 8, 3>;
+        let secrets: Vec<_> = (0..10).map(|_| field.random()).collect();
+        let mut shares = server.split(&secrets).unwrap().map(Some);
+        assert!(shares.iter().all(|share| share.as_ref().unwrap().len() == 4));
+        shares[0] = None;
+        shares[4] = None;
+        shares[7] = None;
+        let recovered = server.recover(&shares).unwrap();
+        assert_eq!(secrets, recovered[..10]);
+        assert!(recovered[10..].iter().all(|s| *s == field.zero()));
+        shares[1] = None;
+        assert_eq!(
+            Error::MPCError(MPCErrorKind::InsufficientShares),
+            server.recover(&shares).unwrap_err()
+        );
+        // parameters that cannot pack the secrets are rejected instead of panicking
+        assert!(Server::<2, 3, 3>.split(&secrets).is_err());
+        assert!(Server::<4, 3, 2>.split(&secrets).is_err());
+        assert!(Server::<2, 3, 0>.split(&secrets).is_err());
+    }
+
+    check(BinaryField::<16, 1>::random_new());
+    check(PrimeModulus::<1>::from_random(61).random_make());
+}
@@ -0,0 +1,71 @@
+//! implements proactive share refresh
+//! Proactive Secret Sharing Or: How to Cope With Perpetual Leakage
+//! https://link.springer.com/chapter/10.1007/3-540-44750-4_27
+
+use super::SSServer;
+use crate::math::galois::FiniteField;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::MultiParty;
+
+/// re-randomizes shares of a (T, N) sharing among N parties
+pub struct Refresher<const N: usize, S> {
+    party: MultiParty<N>,
+    server: S,
+}
+
+impl<const N: usize, S> Refresher<N, S> {
+    pub fn new(party: MultiParty<N>, server: S) -> Self {
+        Self { party, server }
+    }
+
+    /// adds a fresh sharing of zero to `share`, which keeps the secret unchanged
+    pub fn refresh<const T: usize, F: FiniteField>(&self, share: F) -> Result<F>
+    where
+        S: SSServer<T, N, F>,
+    {
+        let me = self.party.id;
+        let zeros = self.server.split(share.zero())?;
+        for (id, zero) in zeros.iter().enumerate().filter(|&(id, _)| id != me) {
+            self.party.send(&id, &zero.encode())?;
+        }
+        let mut share = share + zeros[me];
+        for id in (0..N).filter(|&id| id != me) {
+            share += share
+                .decode(&self.party.recv(&id)?)
+                .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))?;
+        }
+        Ok(share)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::s79::Server;
+    use crate::math::BinaryField;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers = [
+        SocketAddr::from(([127, 0, 0, 1], 9000)),
+        SocketAddr::from(([127, 0, 0, 1], 9001)),
+        SocketAddr::from(([127, 0, 0, 1], 9002)),
+    ];
+
+    let server = Server::<2, 3>;
+    let secret = BinaryField::<16, 1>::random_new();
+    let shares = server.split(secret).unwrap();
+    let refreshed: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                scope.spawn(move || {
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    Refresher::new(party, Server::<2, 3>).refresh(shares[id]).unwrap()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_ne!(shares.to_vec(), refreshed);
+    assert_eq!(secret, server.recover(&[None, Some(refreshed[1]), Some(refreshed[2])]).unwrap());
+    assert_eq!(secret, server.recover(&[Some(refreshed[0]), Some(refreshed[1]), None]).unwrap());
+}
@@ -0,0 +1,110 @@
+//! implements share redistribution to a new threshold and party set
+//! Verifiable Secret Redistribution for Archive Systems
+//! https://ieeexplore.ieee.org/document/1183515
+
+use super::SSServer;
+use crate::math::galois::FiniteField;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::MultiParty;
+
+/// moves a (T, N) sharing to a (T', N') sharing among M parties in total
+/// Every old holder shares its share with the new scheme, and every new
+/// holder recovers its share from the sub-shares with the old scheme.
+pub struct Resharer<const M: usize, S, R> {
+    party: MultiParty<M>,
+    old: S,
+    new: R,
+}
+
+impl<const M: usize, S, R> Resharer<M, S, R> {
+    pub fn new(party: MultiParty<M>, old: S, new: R) -> Self {
+        Self { party, old, new }
+    }
+
+    /// redistributes the secret from the old holders to the new holders
+    /// * `olds` - `olds[i]` is the id of the party holding share `i` of the old sharing.
+    /// * `news` - `news[j]` is the id of the party receiving share `j` of the new sharing.
+    /// * `share` - the old share of this party, or any element of the field if it holds none.
+    ///
+    /// Returns the new share if this party is a new holder.
+    pub fn reshare<const T: usize, const N: usize, const T2: usize, const N2: usize, F>(
+        &self,
+        olds: &[usize; N],
+        news: &[usize; N2],
+        share: F,
+    ) -> Result<Option<F>>
+    where
+        F: FiniteField,
+        S: SSServer<T, N, F>,
+        R: SSServer<T2, N2, F>,
+    {
+        let me = self.party.id;
+        let mut own = None;
+        if olds.contains(&me) {
+            let subshares = self.new.split(share)?;
+            for (subshare, &id) in subshares.iter().zip(news) {
+                if id == me {
+                    own = Some(*subshare);
+                } else {
+                    self.party.send(&id, &subshare.encode())?;
+                }
+            }
+        }
+        if !news.contains(&me) {
+            return Ok(None);
+        }
+        let mut subshares = [None; N];
+        for (subshare, &id) in subshares.iter_mut().zip(olds) {
+            *subshare = if id == me {
+                own
+            } else {
+                let bytes = self.party.recv(&id)?;
+                Some(
+                    share
+                        .decode(&bytes)
+                        .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))?,
+                )
+            };
+        }
+        Ok(Some(self.old.recover(&subshares)?))
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::s79::Server;
+    use crate::math::PrimeModulus;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers: [SocketAddr; 5] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9010 + i as u16)));
+    let olds = [0, 1, 2];
+    let news = [1, 2, 3, 4];
+
+    let secret = PrimeModulus::<1>::from_random(61).random_make();
+    let shares = Server::<2, 3>.split(secret).unwrap();
+    let reshared: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..5)
+            .map(|id| {
+                scope.spawn(move || {
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    let resharer = Resharer::new(party, Server::<2, 3>, Server::<3, 4>);
+                    let share = olds
+                        .iter()
+                        .position(|&old| old == id)
+                        .map_or(secret.zero(), |i| shares[i]);
+                    resharer.reshare(&olds, &news, share).unwrap()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    assert_eq!(None, reshared[0]);
+    let server = Server::<3, 4>;
+    let mut shares = [reshared[1], reshared[2], reshared[3], reshared[4]];
+    shares[2] = None;
+    assert_eq!(secret, server.recover(&shares).unwrap());
+    shares[0] = None;
+    assert!(server.recover(&shares).is_err());
+}
@@ -0,0 +1,54 @@
+//! implements Shamir secret sharing algorithm
+//! How to Share a Secret
+//! https://dl.acm.org/doi/10.1145/359168.359176
+
+use super::SSServer;
+use crate::math::galois::FiniteField;
+use crate::math::lagrange;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+
+/// shares a secret as evaluations of a random polynomial of degree T - 1,
+/// where the share of party `i` is evaluated at `i + 1`
+pub struct Server<const T: usize, const N: usize>;
+
+impl<const T: usize, const N: usize, F: FiniteField> SSServer<T, N, F> for Server<T, N> {
+    fn split(&self, secret: F) -> Result<[F; N]> {
+        let coeffs = lagrange::random(secret, T - 1);
+        Ok(std::array::from_fn(|i| {
+            lagrange::evaluate(&coeffs, &secret.embed(i as u64 + 1))
+        }))
+    }
+
+    fn recover(&self, shares: &[Option<F>; N]) -> Result<F> {
+        let (xs, ys): (Vec<F>, Vec<F>) = shares
+            .iter()
+            .enumerate()
+            .filter_map(|(i, share)| share.map(|share| (share.embed(i as u64 + 1), share)))
+            .unzip();
+        if xs.len() < T || xs.is_empty() {
+            return Err(Error::MPCError(MPCErrorKind::InsufficientShares));
+        }
+        Ok(lagrange::interpolate(&xs, &ys, &ys[0].zero()))
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::math::{BinaryField, PrimeModulus};
+
+    fn check<F: FiniteField>(secret: F) {
+        let server = Server::<3, 5>;
+        let mut shares = server.split(secret).unwrap().map(Some);
+        shares[1] = None;
+        shares[3] = None;
+        assert_eq!(secret, server.recover(&shares).unwrap());
+        shares[4] = None;
+        assert_eq!(
+            Error::MPCError(MPCErrorKind::InsufficientShares),
+            server.recover(&shares).unwrap_err()
+        );
+    }
+
+    check(BinaryField::<8, 1>::random_new());
+    check(PrimeModulus::<1>::from_random(61).random_make());
+}
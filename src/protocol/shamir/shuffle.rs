@@ -0,0 +1,159 @@
+//! implements an oblivious shuffle of shared rows with honest majority
+//! Round-Efficient Oblivious Database Manipulation
+//! https://eprint.iacr.org/2011/429.pdf
+//! For every set of N - T + 1 parties, its members agree on a random
+//! permutation, turn their Shamir shares into additive shares by Lagrange
+//! coefficients, permute them and reshare them to all parties. Any T - 1
+//! parties miss some set, whose permutation hides the composition from them.
+//! Shares must be evaluations at `i + 1` as in `s79`.
+
+use super::{bgw88::Evaluator, SSServer};
+use crate::math::galois::FiniteField;
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::math::lagrange;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use blake2::{Blake2b512, Digest};
+use std::io::ErrorKind;
+
+/// samples a permutation of `len` elements by Fisher-Yates from a seed
+fn permutation(seed: &[u8; 16], len: usize) -> Vec<usize> {
+    let mut perm: Vec<usize> = (0..len).collect();
+    for i in (1..len).rev() {
+        let hash = Blake2b512::new()
+            .chain_update(seed)
+            .chain_update((i as u64).to_le_bytes())
+            .finalize();
+        let draw = u128::from_le_bytes(hash[..16].try_into().unwrap());
+        perm.swap(i, (draw % (i as u128 + 1)) as usize);
+    }
+    perm
+}
+
+/// lists the sets of `size` parties out of `n` in lexicographic order
+fn subsets(n: usize, size: usize) -> Vec<Vec<usize>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    (size - 1..n)
+        .flat_map(|last| {
+            subsets(last, size - 1).into_iter().map(move |mut set| {
+                set.push(last);
+                set
+            })
+        })
+        .collect()
+}
+
+impl<const T: usize, const N: usize, F, S> Evaluator<T, N, F, S>
+where
+    F: FiniteField,
+    S: SSServer<T, N, F>,
+{
+    /// permutes the shared rows of `members` by a permutation known to them only
+    fn permute(&self, members: &[usize], rows: &[Vec<F>], width: usize) -> Result<Vec<Vec<F>>> {
+        let (me, field) = (self.id(), *self.field());
+        let malformed = || Error::MPCError(MPCErrorKind::MalformedMessage);
+        let mut additive = vec![];
+        if members.contains(&me) {
+            let party = self.party();
+            let seed = if me == members[0] {
+                let mut seed = [0u8; 16];
+                OsRng.fill_bytes(&mut seed);
+                for id in &members[1..] {
+                    party.send(id, &seed)?;
+                }
+                seed
+            } else {
+                party
+                    .recv(&members[0])?
+                    .try_into()
+                    .map_err(|_| malformed())?
+            };
+            let xs: Vec<F> = members.iter().map(|&i| field.embed(i as u64 + 1)).collect();
+            let position = members.iter().position(|&i| i == me).unwrap();
+            let lambda = lagrange::basis(&xs, &field.zero())[position];
+            additive = permutation(&seed, rows.len())
+                .into_iter()
+                .flat_map(|j| rows[j].iter().map(|x| lambda * x))
+                .collect();
+        }
+        // reshare the additive shares and add them up
+        let mut flat = vec![field.zero(); rows.len() * width];
+        for &owner in members {
+            let shares = self.input(owner, &additive)?;
+            if shares.len() != flat.len() {
+                return Err(malformed());
+            }
+            flat.iter_mut().zip(shares).for_each(|(x, s)| *x += s);
+        }
+        Ok(flat.chunks(width).map(<[F]>::to_vec).collect())
+    }
+
+    /// shuffles shared rows of equal width by a permutation that no T - 1 parties know
+    pub fn shuffle(&self, rows: &[Vec<F>]) -> Result<Vec<Vec<F>>> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(Error::IOError(ErrorKind::InvalidInput));
+        }
+        if width == 0 {
+            return Ok(rows.to_vec());
+        }
+        subsets(N, N - T + 1)
+            .iter()
+            .try_fold(rows.to_vec(), |rows, members| {
+                self.permute(members, &rows, width)
+            })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::s79::Server;
+    use crate::math::PrimeModulus;
+    use crate::protocol::party::MultiParty;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    assert_eq!(vec![vec![0, 1], vec![0, 2], vec![1, 2]], subsets(3, 2));
+    let mut sorted = permutation(&[7; 16], 50);
+    assert_ne!((0..50).collect::<Vec<_>>(), sorted);
+    sorted.sort();
+    assert_eq!((0..50).collect::<Vec<_>>(), sorted);
+
+    let field = PrimeModulus::<1>::from_random(62).random_make();
+    let rows: Vec<[u64; 2]> = (0..20).map(|i| [i, i * i + 1]).collect();
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9220 + i as u16)));
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                let rows = &rows;
+                scope.spawn(move || {
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    let evaluator = Evaluator::new(party, Server::<2, 3>, field);
+                    let values: Vec<_> = match id {
+                        0 => rows.iter().flatten().map(|&v| field.embed(v)).collect(),
+                        _ => vec![],
+                    };
+                    let shares = evaluator.input(0, &values).unwrap();
+                    let rows: Vec<Vec<_>> = shares.chunks(2).map(<[_]>::to_vec).collect();
+                    let shuffled = evaluator.shuffle(&rows).unwrap();
+                    let ragged = [rows[0].clone(), rows[1][..1].to_vec()];
+                    assert!(evaluator.shuffle(&ragged).is_err());
+                    evaluator.open(&shuffled.concat()).unwrap()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let expected: Vec<_> = rows.iter().map(|row| row.map(|v| field.embed(v))).collect();
+    let mut shuffled: Vec<_> = results[0].chunks(2).map(|row| [row[0], row[1]]).collect();
+    assert!(results.iter().all(|result| *result == results[0]));
+    // rows stay intact, and are moved with overwhelming probability
+    assert_ne!(expected, shuffled);
+    for row in &expected {
+        let i = shuffled.iter().position(|r| r == row).unwrap();
+        shuffled.swap_remove(i);
+    }
+    assert!(shuffled.is_empty());
+}
@@ -0,0 +1,178 @@
+//! implements an oblivious sort of shared rows by Batcher's bitonic sorting network
+//! Sorting Networks and Their Applications
+//! https://dl.acm.org/doi/10.1145/1468075.1468121
+//! The network compares fixed positions regardless of the values, so that a
+//! comparison of shared keys and a multiplication by the shared outcome
+//! exchange the rows without revealing their order. Comparators of a layer are
+//! independent and run in one batch. Lengths other than powers of two merge
+//! with the largest power of two below the length.
+
+use super::{bgw88::Evaluator, SSServer};
+use crate::math::galois::FiniteField;
+use crate::protocol::error::{Error, Result};
+use std::io::ErrorKind;
+
+/// comparator that moves the lesser value to the first position
+type Comparator = (usize, usize);
+
+fn bitonic_sort(lo: usize, n: usize, ascending: bool, out: &mut Vec<Comparator>) {
+    if n > 1 {
+        let m = n / 2;
+        bitonic_sort(lo, m, !ascending, out);
+        bitonic_sort(lo + m, n - m, ascending, out);
+        bitonic_merge(lo, n, ascending, out);
+    }
+}
+
+fn bitonic_merge(lo: usize, n: usize, ascending: bool, out: &mut Vec<Comparator>) {
+    if n > 1 {
+        let m = 1 << (n - 1).ilog2();
+        for i in lo..lo + n - m {
+            out.push(if ascending { (i, i + m) } else { (i + m, i) });
+        }
+        bitonic_merge(lo, m, ascending, out);
+        bitonic_merge(lo + m, n - m, ascending, out);
+    }
+}
+
+/// arranges the comparators of the network for `n` values into layers of disjoint comparators
+fn network(n: usize) -> Vec<Vec<Comparator>> {
+    let mut comparators = vec![];
+    bitonic_sort(0, n, true, &mut comparators);
+    let mut depths = vec![0; n];
+    let mut layers: Vec<Vec<Comparator>> = vec![];
+    for (a, b) in comparators {
+        let layer = depths[a].max(depths[b]);
+        if layer == layers.len() {
+            layers.push(vec![]);
+        }
+        layers[layer].push((a, b));
+        depths[a] = layer + 1;
+        depths[b] = layer + 1;
+    }
+    layers
+}
+
+impl<const T: usize, const N: usize, F, S> Evaluator<T, N, F, S>
+where
+    F: FiniteField,
+    S: SSServer<T, N, F>,
+{
+    /// sorts shared rows in ascending order of column `key`, whose values lie in `[0, 2^width)`
+    /// Rows must have equal width and contain the column `key`.
+    pub fn sort_rows(&self, rows: &[Vec<F>], key: usize, width: usize) -> Result<Vec<Vec<F>>> {
+        let columns = rows.first().map_or(0, Vec::len);
+        if rows
+            .iter()
+            .any(|row| row.len() != columns || key >= columns)
+        {
+            return Err(Error::IOError(ErrorKind::InvalidInput));
+        }
+        let mut rows = rows.to_vec();
+        for layer in network(rows.len()) {
+            let xs: Vec<F> = layer.iter().map(|&(a, _)| rows[a][key]).collect();
+            let ys: Vec<F> = layer.iter().map(|&(_, b)| rows[b][key]).collect();
+            let swaps = self.less_than(&ys, &xs, width)?;
+            // exchange every column of a pair by the shared outcome
+            let (factors, diffs): (Vec<F>, Vec<F>) = layer
+                .iter()
+                .zip(&swaps)
+                .flat_map(|(&(a, b), swap)| {
+                    let (row_a, row_b) = (&rows[a], &rows[b]);
+                    row_a.iter().zip(row_b).map(move |(x, y)| (*swap, *y - x))
+                })
+                .unzip();
+            let mut products = self.mul(&factors, &diffs)?.into_iter();
+            for &(a, b) in &layer {
+                for k in 0..rows[a].len() {
+                    let product = products.next().unwrap();
+                    rows[a][k] += product;
+                    rows[b][k] -= product;
+                }
+            }
+        }
+        Ok(rows)
+    }
+
+    /// sorts shared values in `[0, 2^width)` in ascending order
+    pub fn sort(&self, xs: &[F], width: usize) -> Result<Vec<F>> {
+        let rows: Vec<Vec<F>> = xs.iter().map(|x| vec![*x]).collect();
+        Ok(self.sort_rows(&rows, 0, width)?.concat())
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::s79::Server;
+    use crate::math::PrimeModulus;
+    use crate::protocol::party::MultiParty;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    // the network sorts every sequence of bits, hence every sequence by the 0-1 principle
+    for n in 0..=12 {
+        let layers = network(n);
+        for bits in 0..1u32 << n {
+            let mut values: Vec<u32> = (0..n).map(|i| bits >> i & 1).collect();
+            for layer in &layers {
+                for &(a, b) in layer {
+                    if values[b] < values[a] {
+                        values.swap(a, b);
+                    }
+                }
+            }
+            assert!(values.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+
+    let field = PrimeModulus::<1>::from_random(62).random_make();
+    let keys = [41u64, 7, 63, 7, 0, 22, 35];
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9230 + i as u16)));
+    let results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                scope.spawn(move || {
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    let evaluator = Evaluator::new(party, Server::<2, 3>, field);
+                    let values: Vec<_> = match id {
+                        0 => keys
+                            .iter()
+                            .enumerate()
+                            .flat_map(|(i, &k)| [field.embed(k), field.embed(100 + i as u64)])
+                            .collect(),
+                        _ => vec![],
+                    };
+                    let shares = evaluator.input(0, &values).unwrap();
+                    let rows: Vec<Vec<_>> = shares.chunks(2).map(<[_]>::to_vec).collect();
+                    let sorted = evaluator.sort_rows(&rows, 0, 6).unwrap();
+                    // ragged rows and keys out of the rows fail before any message
+                    let ragged = [rows[0].clone(), rows[1][..1].to_vec()];
+                    let invalid = Error::IOError(ErrorKind::InvalidInput);
+                    assert_eq!(Err(invalid), evaluator.sort_rows(&ragged, 0, 6));
+                    assert_eq!(Err(invalid), evaluator.sort_rows(&rows, 2, 6));
+                    evaluator.open(&sorted.concat()).unwrap()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    let mut expected: Vec<(u64, u64)> = keys
+        .iter()
+        .enumerate()
+        .map(|(i, &k)| (k, 100 + i as u64))
+        .collect();
+    expected.sort();
+    for result in results {
+        let rows: Vec<_> = result.chunks(2).map(|row| (row[0], row[1])).collect();
+        let sorted_keys: Vec<_> = expected.iter().map(|&(k, _)| field.embed(k)).collect();
+        assert_eq!(
+            sorted_keys,
+            rows.iter().map(|&(k, _)| k).collect::<Vec<_>>()
+        );
+        // every row keeps its payload
+        for (k, payload) in &expected {
+            assert!(rows.contains(&(field.embed(*k), field.embed(*payload))));
+        }
+    }
+}
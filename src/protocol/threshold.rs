@@ -0,0 +1,50 @@
+//! threshold cryptography over secp256k1
+//! Any T of N parties jointly use a key that no single party holds.
+
+use crate::math::lagrange;
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use k256::{ProjectivePoint, Scalar};
+
+/// share of a jointly generated key held by party `id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyShare<const N: usize> {
+    /// id of the party holding this share
+    pub id: usize,
+    /// number of shares needed to use the key
+    pub threshold: usize,
+    /// share of the secret key
+    pub secret: Scalar,
+    /// joint public key
+    pub public: ProjectivePoint,
+    /// public key shares of all parties, i.e., `secret * G` of party `i` at index `i`
+    pub verifiers: [ProjectivePoint; N],
+}
+
+/// computes Lagrange coefficients at zero for the shares of parties `ids`
+pub(crate) fn lagrange_at_zero(ids: &[usize]) -> Vec<Scalar> {
+    let xs: Vec<_> = ids.iter().map(|&id| Scalar::from(id as u64 + 1)).collect();
+    lagrange::basis(&xs, &Scalar::ZERO)
+}
+
+/// sorts and deduplicates the ids of the parties taking part, which must
+/// lie below N, contain `me` and number at least `threshold`
+pub(crate) fn participants<const N: usize>(
+    ids: &[usize],
+    me: usize,
+    threshold: usize,
+) -> Result<Vec<usize>> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.iter().any(|&id| id >= N) || !ids.contains(&me) {
+        return Err(Error::MPCError(MPCErrorKind::InvalidShare));
+    }
+    if ids.len() < threshold {
+        return Err(Error::MPCError(MPCErrorKind::InsufficientShares));
+    }
+    Ok(ids)
+}
+
+pub mod dkg;
+pub mod elgamal;
+pub mod frost;
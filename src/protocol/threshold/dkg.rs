@@ -0,0 +1,171 @@
+//! implements distributed key generation by joint Feldman secret sharing
+//! Secure Distributed Key Generation for Discrete-Log Based Cryptosystems
+//! https://link.springer.com/chapter/10.1007/3-540-48910-X_21
+//! Every party deals a random secret with Feldman VSS, and the joint key is the
+//! sum of the secrets of all dealers that answered every complaint correctly.
+
+use super::KeyShare;
+use crate::math::weierstrass::{decode_points, decode_scalar, encode_points};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::MultiParty;
+use crate::protocol::shamir::feldman::{self, Dealer};
+use k256::{
+    elliptic_curve::{rand_core::OsRng, Field},
+    Scalar,
+};
+
+/// generates a (T, N) shared key among N parties
+pub struct KeyGenerator<const T: usize, const N: usize> {
+    party: MultiParty<N>,
+}
+
+impl<const T: usize, const N: usize> KeyGenerator<T, N> {
+    pub fn new(party: MultiParty<N>) -> Self {
+        Self { party }
+    }
+
+    /// the underlying party, which can be reused after key generation
+    pub fn into_party(self) -> MultiParty<N> {
+        self.party
+    }
+
+    /// runs the key generation and returns the key share of this party
+    pub fn run(&self) -> Result<KeyShare<N>> {
+        let me = self.party.id;
+        let others = || (0..N).filter(move |&id| id != me);
+        let malformed = Error::MPCError(MPCErrorKind::MalformedMessage);
+
+        // deal a random secret
+        let (shares, commitments) = Dealer::<T, N>.deal(Scalar::random(&mut OsRng));
+        self.party.broadcast(&encode_points(&commitments))?;
+        for id in others() {
+            self.party.send(&id, &shares[id].to_bytes())?;
+        }
+        let mut received = [Scalar::ZERO; N];
+        let mut commits = vec![commitments.to_vec(); N];
+        received[me] = shares[me];
+        for id in others() {
+            commits[id] = decode_points(&self.party.recv(&id)?)
+                .filter(|commits| commits.len() == T)
+                .ok_or(malformed)?;
+            received[id] = decode_scalar(&self.party.recv(&id)?).ok_or(malformed)?;
+        }
+
+        // complain about dealers whose shares are inconsistent with their commitments
+        let complaints: Vec<usize> = others()
+            .filter(|&id| !feldman::verify(&commits[id], me, &received[id]))
+            .collect();
+        self.party.broadcast(&encode_ids(&complaints))?;
+        let mut accusers = vec![vec![]; N];
+        accusers[me] = complaints;
+        for id in others() {
+            accusers[id] = decode_ids(&self.party.recv(&id)?).ok_or(malformed)?;
+        }
+        let accusers: Vec<Vec<usize>> = (0..N)
+            .map(|dealer| {
+                (0..N)
+                    .filter(|&id| accusers[id].contains(&dealer))
+                    .collect()
+            })
+            .collect();
+
+        // answer complaints by publishing the disputed shares
+        let mut answers = vec![];
+        for &id in &accusers[me] {
+            answers.extend((id as u64).to_le_bytes());
+            answers.extend(shares[id].to_bytes());
+        }
+        self.party.broadcast(&answers)?;
+        let mut qualified = [true; N];
+        for dealer in others() {
+            let answers = self.party.recv(&dealer)?;
+            let answers = answers
+                .chunks(40)
+                .map(|chunk| {
+                    let id = u64::from_le_bytes(chunk.get(..8)?.try_into().ok()?) as usize;
+                    Some((id, decode_scalar(chunk.get(8..)?)?))
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or(malformed)?;
+            qualified[dealer] = accusers[dealer].iter().all(|&id| {
+                answers.iter().any(|&(answered, share)| {
+                    answered == id && id < N && feldman::verify(&commits[dealer], id, &share)
+                })
+            });
+            // adopt only a published share that matches the commitments
+            if let Some(&(_, share)) = answers
+                .iter()
+                .find(|&&(id, share)| id == me && feldman::verify(&commits[dealer], me, &share))
+            {
+                received[dealer] = share;
+            }
+        }
+
+        let qualified: Vec<usize> = (0..N).filter(|&id| qualified[id]).collect();
+        Ok(KeyShare {
+            id: me,
+            threshold: T,
+            secret: qualified.iter().map(|&id| received[id]).sum(),
+            public: qualified.iter().map(|&id| commits[id][0]).sum(),
+            verifiers: std::array::from_fn(|k| {
+                qualified
+                    .iter()
+                    .map(|&id| feldman::public_share(&commits[id], k))
+                    .sum()
+            }),
+        })
+    }
+}
+
+/// encodes a list of party ids
+fn encode_ids(ids: &[usize]) -> Vec<u8> {
+    ids.iter()
+        .flat_map(|&id| (id as u64).to_le_bytes())
+        .collect()
+}
+
+/// decodes a list of party ids
+fn decode_ids(bytes: &[u8]) -> Option<Vec<usize>> {
+    if !bytes.len().is_multiple_of(8) {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()) as usize)
+            .collect(),
+    )
+}
+
+#[test]
+fn test_correctness() {
+    use super::lagrange_at_zero;
+    use k256::{elliptic_curve::ops::MulByGenerator, ProjectivePoint};
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9020 + i as u16)));
+    let keys: Vec<KeyShare<3>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                scope.spawn(move || {
+                    let party = MultiParty::new(id, &peers).unwrap();
+                    KeyGenerator::<2, 3>::new(party).run().unwrap()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for key in &keys {
+        assert_eq!(keys[0].public, key.public);
+        assert_eq!(keys[0].verifiers, key.verifiers);
+        assert_eq!(
+            key.verifiers[key.id],
+            ProjectivePoint::mul_by_generator(&key.secret)
+        );
+    }
+    let lambda = lagrange_at_zero(&[0, 2]);
+    let secret = lambda[0] * keys[0].secret + lambda[1] * keys[2].secret;
+    assert_eq!(keys[0].public, ProjectivePoint::mul_by_generator(&secret));
+}
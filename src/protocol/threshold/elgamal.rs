@@ -0,0 +1,158 @@
+//! implements threshold ElGamal and ECDH decryption
+//! Threshold Cryptosystems
+//! https://link.springer.com/chapter/10.1007/0-387-34805-0_28
+//! Any T key holders jointly compute `secret * ephemeral` from their partial
+//! decryptions, without rebuilding the secret key.
+
+use super::{lagrange_at_zero, participants, KeyShare};
+use crate::math::weierstrass::{decode_point, encode_point};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::MultiParty;
+use blake2::{Blake2b512, Digest};
+use k256::{
+    elliptic_curve::{ops::MulByGenerator, rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+
+/// ElGamal ciphertext of a point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ciphertext {
+    pub ephemeral: ProjectivePoint,
+    pub body: ProjectivePoint,
+}
+
+/// encrypts a point to the joint public key
+pub fn encrypt(public: &ProjectivePoint, message: &ProjectivePoint) -> Ciphertext {
+    let r = Scalar::random(&mut OsRng);
+    Ciphertext {
+        ephemeral: ProjectivePoint::mul_by_generator(&r),
+        body: *message + *public * r,
+    }
+}
+
+/// ECDH hybrid ciphertext of a byte string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sealed {
+    pub ephemeral: ProjectivePoint,
+    pub body: Vec<u8>,
+}
+
+/// encrypts a byte string to the joint public key
+pub fn seal(public: &ProjectivePoint, message: &[u8]) -> Sealed {
+    let r = Scalar::random(&mut OsRng);
+    let ephemeral = ProjectivePoint::mul_by_generator(&r);
+    Sealed {
+        ephemeral,
+        body: mask(&(*public * r), &ephemeral, message),
+    }
+}
+
+/// xors the message with a key stream derived from the ECDH secret
+fn mask(shared: &ProjectivePoint, ephemeral: &ProjectivePoint, message: &[u8]) -> Vec<u8> {
+    message
+        .chunks(64)
+        .enumerate()
+        .flat_map(|(counter, chunk)| {
+            let mut hasher = Blake2b512::new();
+            hasher.update(encode_point(shared));
+            hasher.update(encode_point(ephemeral));
+            hasher.update((counter as u64).to_le_bytes());
+            let stream = hasher.finalize();
+            chunk
+                .iter()
+                .zip(stream)
+                .map(|(m, k)| m ^ k)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// decrypts jointly with other key holders
+pub struct Decryptor<const N: usize> {
+    party: MultiParty<N>,
+    key: KeyShare<N>,
+}
+
+impl<const N: usize> Decryptor<N> {
+    pub fn new(party: MultiParty<N>, key: KeyShare<N>) -> Self {
+        Self { party, key }
+    }
+
+    /// jointly computes `secret * ephemeral` among `holders`, which must
+    /// contain this party and at least T parties in total
+    fn exchange(&self, holders: &[usize], ephemeral: &ProjectivePoint) -> Result<ProjectivePoint> {
+        let me = self.party.id;
+        let holders = &participants::<N>(holders, me, self.key.threshold)?;
+        let partial = *ephemeral * self.key.secret;
+        for &id in holders.iter().filter(|&&id| id != me) {
+            self.party.send(&id, &encode_point(&partial))?;
+        }
+        let lambda = lagrange_at_zero(holders);
+        holders
+            .iter()
+            .zip(lambda)
+            .try_fold(ProjectivePoint::IDENTITY, |acc, (&id, lambda)| {
+                let partial = if id == me {
+                    partial
+                } else {
+                    decode_point(&self.party.recv(&id)?)
+                        .ok_or(Error::MPCError(MPCErrorKind::MalformedMessage))?
+                };
+                Ok(acc + partial * lambda)
+            })
+    }
+
+    /// decrypts an ElGamal ciphertext among `holders`
+    pub fn decrypt(&self, holders: &[usize], cipher: &Ciphertext) -> Result<ProjectivePoint> {
+        Ok(cipher.body - self.exchange(holders, &cipher.ephemeral)?)
+    }
+
+    /// decrypts an ECDH hybrid ciphertext among `holders`
+    pub fn open(&self, holders: &[usize], sealed: &Sealed) -> Result<Vec<u8>> {
+        let shared = self.exchange(holders, &sealed.ephemeral)?;
+        Ok(mask(&shared, &sealed.ephemeral, &sealed.body))
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::dkg::KeyGenerator;
+    use crate::math::weierstrass::hash_to_curve;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9030 + i as u16)));
+    let decryptors: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                scope.spawn(move || {
+                    let keygen = KeyGenerator::<2, 3>::new(MultiParty::new(id, &peers).unwrap());
+                    let key = keygen.run().unwrap();
+                    Decryptor::new(keygen.into_party(), key)
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let public = decryptors[0].key.public;
+    let point = hash_to_curve(b"answer");
+    let cipher = encrypt(&public, &point);
+    let message = b"a query answer longer than one block of the key stream".repeat(2);
+    let sealed = seal(&public, &message);
+    assert!(decryptors[1].decrypt(&[1], &cipher).is_err());
+    // duplicates, outsiders and sets without this party are rejected before any message
+    assert!(decryptors[1].decrypt(&[1, 1], &cipher).is_err());
+    assert!(decryptors[1].decrypt(&[1, 3], &cipher).is_err());
+    assert!(decryptors[1].decrypt(&[0, 2], &cipher).is_err());
+    thread::scope(|scope| {
+        for decryptor in decryptors.into_iter().filter(|d| d.party.id != 1) {
+            let (cipher, sealed, message) = (&cipher, &sealed, &message);
+            scope.spawn(move || {
+                assert_eq!(point, decryptor.decrypt(&[0, 2], cipher).unwrap());
+                assert_eq!(*message, decryptor.open(&[0, 2], sealed).unwrap());
+            });
+        }
+    });
+}
@@ -0,0 +1,244 @@
+//! implements FROST threshold Schnorr signatures
+//! FROST: Flexible Round-Optimized Schnorr Threshold Signatures
+//! https://eprint.iacr.org/2020/852.pdf
+
+use super::{lagrange_at_zero, participants, KeyShare};
+use crate::math::weierstrass::{decode_point, decode_scalar, encode_point, hash_to_scalar};
+use crate::protocol::error::{Error, MPCErrorKind, Result};
+use crate::protocol::party::MultiParty;
+use k256::{
+    elliptic_curve::{ops::MulByGenerator, rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+
+/// Schnorr signature `(R, z)` with `z * G = R + c * Y`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub r: ProjectivePoint,
+    pub z: Scalar,
+}
+
+/// secret nonces of one signing session, which must never be reused
+#[derive(Debug)]
+pub struct Nonce {
+    hiding: Scalar,
+    binding: Scalar,
+}
+
+/// public commitments to the nonces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Commitment {
+    pub hiding: ProjectivePoint,
+    pub binding: ProjectivePoint,
+}
+
+/// samples nonces and their commitments for the first round
+pub fn commit() -> (Nonce, Commitment) {
+    let nonce = Nonce {
+        hiding: Scalar::random(&mut OsRng),
+        binding: Scalar::random(&mut OsRng),
+    };
+    let commitment = Commitment {
+        hiding: ProjectivePoint::mul_by_generator(&nonce.hiding),
+        binding: ProjectivePoint::mul_by_generator(&nonce.binding),
+    };
+    (nonce, commitment)
+}
+
+/// computes the binding factor of every signer from all commitments sorted by id
+fn binding_factors(commitments: &[(usize, Commitment)], msg: &[u8]) -> Vec<Scalar> {
+    let encoded: Vec<u8> = commitments
+        .iter()
+        .flat_map(|(id, commitment)| {
+            [
+                (*id as u64).to_le_bytes().to_vec(),
+                encode_point(&commitment.hiding),
+                encode_point(&commitment.binding),
+            ]
+            .concat()
+        })
+        .collect();
+    commitments
+        .iter()
+        .map(|(id, _)| hash_to_scalar(&[b"rho", &(*id as u64).to_le_bytes(), msg, &encoded]))
+        .collect()
+}
+
+/// computes the group commitment `R` and the challenge `c`
+fn challenge(
+    public: &ProjectivePoint,
+    commitments: &[(usize, Commitment)],
+    factors: &[Scalar],
+    msg: &[u8],
+) -> (ProjectivePoint, Scalar) {
+    let r = commitments
+        .iter()
+        .zip(factors)
+        .map(|((_, commitment), rho)| commitment.hiding + commitment.binding * rho)
+        .sum();
+    let c = hash_to_scalar(&[b"chal", &encode_point(&r), &encode_point(public), msg]);
+    (r, c)
+}
+
+/// computes the signature share of `key` in the second round
+/// `commitments` must be sorted by id and contain the commitment of this signer.
+pub fn partial_sign<const N: usize>(
+    key: &KeyShare<N>,
+    nonce: Nonce,
+    commitments: &[(usize, Commitment)],
+    msg: &[u8],
+) -> Result<Scalar> {
+    let ids: Vec<usize> = commitments.iter().map(|(id, _)| *id).collect();
+    let index = ids
+        .iter()
+        .position(|&id| id == key.id)
+        .ok_or(Error::MPCError(MPCErrorKind::InvalidShare))?;
+    let factors = binding_factors(commitments, msg);
+    let (_, c) = challenge(&key.public, commitments, &factors, msg);
+    let lambda = lagrange_at_zero(&ids)[index];
+    Ok(nonce.hiding + nonce.binding * factors[index] + lambda * key.secret * c)
+}
+
+/// verifies the signature shares against the public key shares in `key`
+/// and aggregates them into a signature, which is verified before it is returned
+/// `commitments` must be sorted by id with one signature share per commitment.
+pub fn aggregate<const N: usize>(
+    key: &KeyShare<N>,
+    commitments: &[(usize, Commitment)],
+    partials: &[Scalar],
+    msg: &[u8],
+) -> Result<Signature> {
+    let ids: Vec<usize> = commitments.iter().map(|(id, _)| *id).collect();
+    let sorted = ids.windows(2).all(|pair| pair[0] < pair[1]);
+    if partials.len() != ids.len() || !sorted || ids.iter().any(|&id| id >= N) {
+        return Err(Error::MPCError(MPCErrorKind::InvalidShare));
+    }
+    let factors = binding_factors(commitments, msg);
+    let (r, c) = challenge(&key.public, commitments, &factors, msg);
+    let lambda = lagrange_at_zero(&ids);
+    for (i, ((id, commitment), z)) in commitments.iter().zip(partials).enumerate() {
+        let expected = commitment.hiding
+            + commitment.binding * factors[i]
+            + key.verifiers[*id] * (lambda[i] * c);
+        if ProjectivePoint::mul_by_generator(z) != expected {
+            return Err(Error::MPCError(MPCErrorKind::InvalidShare));
+        }
+    }
+    let signature = Signature {
+        r,
+        z: partials.iter().sum(),
+    };
+    if !verify(&key.public, msg, &signature) {
+        return Err(Error::MPCError(MPCErrorKind::InvalidShare));
+    }
+    Ok(signature)
+}
+
+/// verifies a signature on `msg` under the public key
+pub fn verify(public: &ProjectivePoint, msg: &[u8], signature: &Signature) -> bool {
+    let c = hash_to_scalar(&[
+        b"chal",
+        &encode_point(&signature.r),
+        &encode_point(public),
+        msg,
+    ]);
+    ProjectivePoint::mul_by_generator(&signature.z) == signature.r + *public * c
+}
+
+/// signs jointly with other key holders
+pub struct Signer<const N: usize> {
+    party: MultiParty<N>,
+    key: KeyShare<N>,
+}
+
+impl<const N: usize> Signer<N> {
+    pub fn new(party: MultiParty<N>, key: KeyShare<N>) -> Self {
+        Self { party, key }
+    }
+
+    /// signs `msg` among `signers`, which must contain this party and at least T parties
+    pub fn sign(&self, signers: &[usize], msg: &[u8]) -> Result<Signature> {
+        let me = self.party.id;
+        let signers = participants::<N>(signers, me, self.key.threshold)?;
+        let others = || signers.iter().copied().filter(move |&id| id != me);
+        let malformed = Error::MPCError(MPCErrorKind::MalformedMessage);
+
+        // round one: exchange nonce commitments
+        let (nonce, commitment) = commit();
+        let encoded = [
+            encode_point(&commitment.hiding),
+            encode_point(&commitment.binding),
+        ]
+        .concat();
+        for id in others() {
+            self.party.send(&id, &encoded)?;
+        }
+        let mut commitments = Vec::with_capacity(signers.len());
+        for &id in &signers {
+            if id == me {
+                commitments.push((id, commitment));
+                continue;
+            }
+            let bytes = self.party.recv(&id)?;
+            let (hiding, binding) = bytes.split_at_checked(33).ok_or(malformed)?;
+            commitments.push((
+                id,
+                Commitment {
+                    hiding: decode_point(hiding).ok_or(malformed)?,
+                    binding: decode_point(binding).ok_or(malformed)?,
+                },
+            ));
+        }
+
+        // round two: exchange signature shares
+        let partial = partial_sign(&self.key, nonce, &commitments, msg)?;
+        for id in others() {
+            self.party.send(&id, &partial.to_bytes())?;
+        }
+        let mut partials = Vec::with_capacity(signers.len());
+        for &id in &signers {
+            partials.push(if id == me {
+                partial
+            } else {
+                decode_scalar(&self.party.recv(&id)?).ok_or(malformed)?
+            });
+        }
+        aggregate(&self.key, &commitments, &partials, msg)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::dkg::KeyGenerator;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let peers: [SocketAddr; 3] =
+        std::array::from_fn(|i| SocketAddr::from(([127, 0, 0, 1], 9040 + i as u16)));
+    let msg = b"catalog entry #42";
+    let signatures: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..3)
+            .map(|id| {
+                scope.spawn(move || {
+                    let keygen = KeyGenerator::<2, 3>::new(MultiParty::new(id, &peers).unwrap());
+                    let key = keygen.run().unwrap();
+                    let signer = Signer::new(keygen.into_party(), key);
+                    if id == 1 {
+                        assert!(signer.sign(&[1], msg).is_err());
+                        // a repeated id does not count towards the threshold
+                        assert!(signer.sign(&[1, 1], msg).is_err());
+                        assert!(signer.sign(&[1, 3], msg).is_err());
+                        assert!(signer.sign(&[0, 2], msg).is_err());
+                        return None;
+                    }
+                    Some((key.public, signer.sign(&[2, 0], msg).unwrap()))
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (public, signature) in signatures.into_iter().flatten() {
+        assert!(verify(&public, msg, &signature));
+        assert!(!verify(&public, b"catalog entry #43", &signature));
+    }
+}
@@ -0,0 +1,184 @@
+//! implements Yao's garbled circuits for two parties
+//! How to Generate and Exchange Secrets
+//! https://ieeexplore.ieee.org/document/4568207
+//! The garbler garbles a boolean circuit and sends it with the labels of its
+//! own inputs, while the evaluator obtains the labels of its inputs by OT.
+
+use super::circuit::Circuit;
+use super::error::{Error, MPCErrorKind, Result};
+use super::ot::{Choice, OTReceive, OTSend};
+use super::party::TwoParty;
+
+/// wire label, whose least significant bit is the permutation bit
+pub type Label = u128;
+
+pub mod zre15;
+
+/// encodes labels into little-endian bytes
+fn encode_labels(labels: &[Label]) -> Vec<u8> {
+    labels
+        .iter()
+        .flat_map(|label| label.to_le_bytes())
+        .collect()
+}
+
+/// decodes `count` labels from bytes
+fn decode_labels(bytes: &[u8], count: usize) -> Result<Vec<Label>> {
+    if bytes.len() != count * 16 {
+        return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+    }
+    Ok(bytes
+        .chunks_exact(16)
+        .map(|chunk| Label::from_le_bytes(chunk.try_into().unwrap()))
+        .collect())
+}
+
+/// decodes `count` bits from one byte each
+fn decode_bits(bytes: &[u8], count: usize) -> Result<Vec<bool>> {
+    if bytes.len() != count || bytes.iter().any(|&byte| byte > 1) {
+        return Err(Error::MPCError(MPCErrorKind::MalformedMessage));
+    }
+    Ok(bytes.iter().map(|&byte| byte == 1).collect())
+}
+
+/// garbles circuits whose first input belongs to the garbler
+/// and second input to the evaluator
+pub struct Garbler<S> {
+    party: TwoParty,
+    ot: S,
+}
+
+impl<S: OTSend<2, 16>> Garbler<S> {
+    pub fn new(party: TwoParty, ot: S) -> Self {
+        Self { party, ot }
+    }
+
+    /// evaluates the circuit jointly on the input of this party
+    /// and returns the output, which the evaluator learns as well
+    pub fn run(&self, circuit: &Circuit, inputs: &[bool]) -> Result<Vec<bool>> {
+        assert_eq!(circuit.inputs.len(), 2, "the circuit must have two inputs");
+        assert_eq!(circuit.inputs[0], inputs.len());
+        let (pairs, garbled) = zre15::garble(circuit);
+        let (mine, theirs) = pairs.split_at(inputs.len());
+        self.party
+            .push(&encode_labels(garbled.tables.as_flattened()))?;
+        let labels: Vec<Label> = mine
+            .iter()
+            .zip(inputs)
+            .map(|(pair, &bit)| pair[bit as usize])
+            .collect();
+        self.party.push(&encode_labels(&labels))?;
+        let decoding: Vec<u8> = garbled.decoding.iter().map(|&bit| bit as u8).collect();
+        self.party.push(&decoding)?;
+        for pair in theirs {
+            self.ot.send(&pair.map(Label::to_le_bytes))?;
+        }
+        decode_bits(&self.party.pull()?, decoding.len())
+    }
+}
+
+/// evaluates circuits garbled by the other party
+pub struct Evaluator<R> {
+    party: TwoParty,
+    ot: R,
+}
+
+impl<R: OTReceive<2, 16>> Evaluator<R> {
+    pub fn new(party: TwoParty, ot: R) -> Self {
+        Self { party, ot }
+    }
+
+    /// evaluates the circuit jointly on the input of this party
+    /// and returns the output, which the garbler learns as well
+    pub fn run(&self, circuit: &Circuit, inputs: &[bool]) -> Result<Vec<bool>> {
+        assert_eq!(circuit.inputs.len(), 2, "the circuit must have two inputs");
+        assert_eq!(circuit.inputs[1], inputs.len());
+        let tables: Vec<[Label; 2]> = decode_labels(&self.party.pull()?, 2 * circuit.and_count())?
+            .chunks_exact(2)
+            .map(|pair| [pair[0], pair[1]])
+            .collect();
+        let mut labels = decode_labels(&self.party.pull()?, circuit.inputs[0])?;
+        let decoding = decode_bits(&self.party.pull()?, circuit.output_wires().len())?;
+        for &bit in inputs {
+            let label = self.ot.receive(&Choice::new(bit as usize).unwrap())?;
+            labels.push(Label::from_le_bytes(label));
+        }
+        let outputs = zre15::decode(&zre15::evaluate(circuit, &tables, &labels), &decoding);
+        let bytes: Vec<u8> = outputs.iter().map(|&bit| bit as u8).collect();
+        self.party.push(&bytes)?;
+        Ok(outputs)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::circuit::{bristol, to_bits as bits};
+    use super::ot::co15;
+    use std::net::SocketAddr;
+    use std::thread;
+
+    let width = 16;
+    let (adder, comparator) = (Circuit::adder(width), Circuit::comparator(width));
+    // AES-128 in Bristol Fashion, where the garbler holds the key and the evaluator the block
+    let aes = bristol::parse(&bristol::write(&Circuit::aes128())).unwrap();
+    let hex = |hex: &str| -> Vec<bool> {
+        (0..hex.len())
+            .step_by(2)
+            .flat_map(|i| bits(u128::from_str_radix(&hex[i..i + 2], 16).unwrap(), 8))
+            .collect()
+    };
+
+    let cases: Vec<(&Circuit, Vec<bool>, Vec<bool>)> = vec![
+        (&adder, bits(40000, width), bits(30000, width)),
+        (&comparator, bits(1234, width), bits(4321, width)),
+        (&comparator, bits(4321, width), bits(1234, width)),
+        (&comparator, bits(777, width), bits(777, width)),
+        (
+            &aes,
+            hex("000102030405060708090a0b0c0d0e0f"),
+            hex("00112233445566778899aabbccddeeff"),
+        ),
+    ];
+    let peers = [
+        SocketAddr::from(([127, 0, 0, 1], 9080)),
+        SocketAddr::from(([127, 0, 0, 1], 9081)),
+    ];
+    let ot_peers = [
+        SocketAddr::from(([127, 0, 0, 1], 9082)),
+        SocketAddr::from(([127, 0, 0, 1], 9083)),
+    ];
+    let cases = &cases;
+    let (garbled, evaluated) = thread::scope(|scope| {
+        let garbler = scope.spawn(move || {
+            let garbler = Garbler::new(
+                TwoParty::new(0, &peers).unwrap(),
+                co15::Sender(TwoParty::new(0, &ot_peers).unwrap()),
+            );
+            cases
+                .iter()
+                .map(|(circuit, a, _)| garbler.run(circuit, a).unwrap())
+                .collect::<Vec<_>>()
+        });
+        let evaluator = scope.spawn(move || {
+            let evaluator = Evaluator::new(
+                TwoParty::new(1, &peers).unwrap(),
+                co15::Receiver(TwoParty::new(1, &ot_peers).unwrap()),
+            );
+            cases
+                .iter()
+                .map(|(circuit, _, b)| evaluator.run(circuit, b).unwrap())
+                .collect::<Vec<_>>()
+        });
+        (garbler.join().unwrap(), evaluator.join().unwrap())
+    });
+    let expected: Vec<Vec<bool>> = cases
+        .iter()
+        .map(|(circuit, a, b)| circuit.eval(&[a.as_slice(), b].concat()))
+        .collect();
+    assert_eq!(bits(70000, width + 1), expected[0]);
+    assert_eq!(vec![vec![true], vec![false], vec![false]], expected[1..4]);
+    // the example vector of FIPS-197 Appendix C.1
+    assert_eq!(hex("69c4e0d86a7b0430d8cdb78070b4c55a"), expected[4]);
+    assert_eq!(expected, garbled);
+    assert_eq!(expected, evaluated);
+}
@@ -0,0 +1,141 @@
+//! implements half-gates garbling with free-XOR
+//! Two Halves Make a Whole: Reducing Data Transfer in Garbled Circuits using Half Gates
+//! https://eprint.iacr.org/2014/756.pdf
+
+use super::Label;
+use crate::math::gauss::rand_core::{OsRng, RngCore};
+use crate::protocol::circuit::{Circuit, Gate};
+use blake2::{Blake2b512, Digest};
+
+/// garbled tables of the AND gates and decoding bits of the output wires
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Garbled {
+    /// the generator and evaluator half of each AND gate in order
+    pub tables: Vec<[Label; 2]>,
+    /// permutation bits of the zero labels of the output wires
+    pub decoding: Vec<bool>,
+}
+
+/// hashes a label with the tweak of its gate
+fn hash(label: Label, tweak: u64) -> Label {
+    let mut hasher = Blake2b512::new();
+    hasher.update(label.to_le_bytes());
+    hasher.update(tweak.to_le_bytes());
+    Label::from_le_bytes(hasher.finalize()[..16].try_into().unwrap())
+}
+
+/// samples a uniformly random label
+fn random() -> Label {
+    (OsRng.next_u64() as Label) << 64 | OsRng.next_u64() as Label
+}
+
+/// gets the permutation bit of a label
+fn lsb(label: Label) -> bool {
+    label & 1 == 1
+}
+
+/// selects `label` if `bit` is set or zero otherwise
+fn select(bit: bool, label: Label) -> Label {
+    if bit {
+        label
+    } else {
+        0
+    }
+}
+
+/// garbles the circuit, returning the zero and one labels of every input wire
+pub fn garble(circuit: &Circuit) -> (Vec<[Label; 2]>, Garbled) {
+    // the global offset has its permutation bit set, so that labels of a wire differ in it
+    let delta = random() | 1;
+    let count = circuit.inputs.iter().sum();
+    let mut zeros: Vec<Label> = vec![0; circuit.wires];
+    zeros[..count].fill_with(random);
+    let inputs = zeros[..count]
+        .iter()
+        .map(|&zero| [zero, zero ^ delta])
+        .collect();
+    let mut tables = Vec::with_capacity(circuit.and_count());
+    for gate in &circuit.gates {
+        match *gate {
+            Gate::Xor(a, b, out) => zeros[out] = zeros[a] ^ zeros[b],
+            Gate::Inv(a, out) => zeros[out] = zeros[a] ^ delta,
+            // the evaluator holds the label zero for constants
+            Gate::Eq(bit, out) => zeros[out] = select(bit, delta),
+            Gate::Eqw(a, out) => zeros[out] = zeros[a],
+            Gate::And(a, b, out) => {
+                let j = 2 * tables.len() as u64;
+                let (a0, b0) = (zeros[a], zeros[b]);
+                let (pa, pb) = (lsb(a0), lsb(b0));
+                // generator half gate
+                let (ha0, ha1) = (hash(a0, j), hash(a0 ^ delta, j));
+                let generator = ha0 ^ ha1 ^ select(pb, delta);
+                let wg = ha0 ^ select(pa, generator);
+                // evaluator half gate
+                let (hb0, hb1) = (hash(b0, j + 1), hash(b0 ^ delta, j + 1));
+                let evaluator = hb0 ^ hb1 ^ a0;
+                let we = hb0 ^ select(pb, evaluator ^ a0);
+                zeros[out] = wg ^ we;
+                tables.push([generator, evaluator]);
+            }
+        }
+    }
+    let decoding = circuit.output_wires().map(|w| lsb(zeros[w])).collect();
+    (inputs, Garbled { tables, decoding })
+}
+
+/// evaluates the garbled circuit on one label per input wire,
+/// returning the labels of the output wires
+pub fn evaluate(circuit: &Circuit, tables: &[[Label; 2]], inputs: &[Label]) -> Vec<Label> {
+    let mut labels: Vec<Label> = vec![0; circuit.wires];
+    labels[..inputs.len()].copy_from_slice(inputs);
+    let mut tables = tables.iter();
+    let mut j = 0;
+    for gate in &circuit.gates {
+        match *gate {
+            Gate::Xor(a, b, out) => labels[out] = labels[a] ^ labels[b],
+            Gate::Inv(a, out) | Gate::Eqw(a, out) => labels[out] = labels[a],
+            Gate::Eq(_, out) => labels[out] = 0,
+            Gate::And(a, b, out) => {
+                let [generator, evaluator] = *tables.next().expect("too few garbled tables");
+                let (wa, wb) = (labels[a], labels[b]);
+                let wg = hash(wa, j) ^ select(lsb(wa), generator);
+                let we = hash(wb, j + 1) ^ select(lsb(wb), evaluator ^ wa);
+                labels[out] = wg ^ we;
+                j += 2;
+            }
+        }
+    }
+    circuit.output_wires().map(|w| labels[w]).collect()
+}
+
+/// decodes the labels of the output wires into bits
+pub fn decode(labels: &[Label], decoding: &[bool]) -> Vec<bool> {
+    labels
+        .iter()
+        .zip(decoding)
+        .map(|(&label, &bit)| lsb(label) ^ bit)
+        .collect()
+}
+
+#[test]
+fn test_correctness() {
+    // out = (a & b) ^ !c
+    let circuit = Circuit {
+        wires: 6,
+        inputs: vec![2, 1],
+        outputs: vec![1],
+        gates: vec![Gate::And(0, 1, 3), Gate::Inv(2, 4), Gate::Xor(3, 4, 5)],
+    };
+    for x in 0..8 {
+        let bits = [x & 1 == 1, x & 2 == 2, x & 4 == 4];
+        let (inputs, garbled) = garble(&circuit);
+        let labels: Vec<Label> = inputs
+            .iter()
+            .zip(bits)
+            .map(|(pair, bit)| pair[bit as usize])
+            .collect();
+        let outputs = evaluate(&circuit, &garbled.tables, &labels);
+        let expected = (bits[0] && bits[1]) ^ !bits[2];
+        assert_eq!(vec![expected], decode(&outputs, &garbled.decoding));
+    }
+}
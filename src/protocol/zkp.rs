@@ -0,0 +1,61 @@
+//! implements zero-knowledge proofs on secp256k1, made non-interactive by Fiat-Shamir
+//! How To Prove Yourself: Practical Solutions to Identification and Signature Problems
+//! https://link.springer.com/chapter/10.1007/3-540-47721-7_12
+//! A transcript absorbs the statement and the prover's commitments, and
+//! squeezes the verifier's challenges, so that proofs bind to their context.
+
+use crate::math::weierstrass::encode_point;
+use blake2::{Blake2b512, Digest};
+use k256::{
+    elliptic_curve::{bigint::U512, ops::Reduce},
+    ProjectivePoint, Scalar, WideBytes,
+};
+
+pub mod bbbpwm18;
+pub mod cds94;
+pub mod cp92;
+pub mod p91;
+pub mod s89;
+
+/// Fiat-Shamir transcript over Blake2b, whose entries are labeled and length-prefixed
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    hasher: Blake2b512,
+}
+
+impl Transcript {
+    /// starts a transcript for the protocol or application named `label`
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Self {
+            hasher: Blake2b512::new(),
+        };
+        transcript.append(b"dense-transcript", label);
+        transcript
+    }
+
+    pub fn append(&mut self, label: &[u8], bytes: &[u8]) {
+        for part in [label, bytes] {
+            self.hasher.update((part.len() as u64).to_le_bytes());
+            self.hasher.update(part);
+        }
+    }
+
+    pub fn append_point(&mut self, label: &[u8], point: &ProjectivePoint) {
+        self.append(label, &encode_point(point));
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &Scalar) {
+        self.append(label, &scalar.to_bytes());
+    }
+
+    /// derives a challenge from everything absorbed so far, and absorbs it
+    pub fn challenge(&mut self, label: &[u8]) -> Scalar {
+        let mut hasher = self.hasher.clone();
+        hasher.update((label.len() as u64).to_le_bytes());
+        hasher.update(label);
+        let bytes: WideBytes = hasher.finalize();
+        let challenge = <Scalar as Reduce<U512>>::reduce_bytes(&bytes);
+        self.append_scalar(label, &challenge);
+        challenge
+    }
+}
@@ -0,0 +1,365 @@
+//! implements Bulletproofs range proofs on Pedersen commitments, with aggregation
+//! Bulletproofs: Short Proofs for Confidential Transactions and More
+//! https://eprint.iacr.org/2017/1066
+//! The prover shows that m committed values `V_j = v_j * G + r_j * H` lie in
+//! `[0, 2^n)` by committing to their bits, reducing the bit constraints to one
+//! inner product `t = <l, r>`, and proving that inner product in `2 log(nm)`
+//! rounds, each halving the vectors and the vector commitment bases.
+
+use super::p91::{blinding_base, commit, multiply, Generators};
+use super::Transcript;
+use crate::math::weierstrass::{decode_point, decode_scalar, encode_point};
+use crate::store::{ParseError, StoreValue};
+use k256::{
+    elliptic_curve::{rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    /// commitment to the bits
+    pub a: ProjectivePoint,
+    /// commitment to the blinding vectors of the bits
+    pub s: ProjectivePoint,
+    /// commitments to the coefficients of `t(X)`
+    pub t1: ProjectivePoint,
+    pub t2: ProjectivePoint,
+    /// blinding factor of `t(x)`
+    pub tau: Scalar,
+    /// blinding factor of the vector commitment `A + x S`
+    pub mu: Scalar,
+    /// evaluation `t(x) = <l, r>`
+    pub t: Scalar,
+    /// cross terms of the inner product argument
+    pub left: Vec<ProjectivePoint>,
+    pub right: Vec<ProjectivePoint>,
+    /// final entries of both folded vectors
+    pub l: Scalar,
+    pub r: Scalar,
+}
+
+fn powers(x: &Scalar, n: usize) -> Vec<Scalar> {
+    std::iter::successors(Some(Scalar::ONE), |p| Some(p * x))
+        .take(n)
+        .collect()
+}
+
+fn inner(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// checks the number of values and bits, and returns the length of the bit vectors
+fn length(generators: &Generators, values: usize, bits: usize) -> Option<usize> {
+    (values.is_power_of_two() && bits.is_power_of_two() && bits <= 64)
+        .then_some(values * bits)
+        .filter(|&n| n <= generators.len())
+}
+
+/// absorbs the statement and the bit commitments, and derives the challenges y and z
+fn start(
+    transcript: &mut Transcript,
+    bits: usize,
+    commitments: &[ProjectivePoint],
+    a: &ProjectivePoint,
+    s: &ProjectivePoint,
+) -> (Scalar, Scalar) {
+    transcript.append(b"proof", b"bulletproofs");
+    transcript.append(b"bits", &(bits as u64).to_le_bytes());
+    for commitment in commitments {
+        transcript.append_point(b"commitment", commitment);
+    }
+    transcript.append_point(b"a", a);
+    transcript.append_point(b"s", s);
+    (transcript.challenge(b"y"), transcript.challenge(b"z"))
+}
+
+/// folds points into `lo * x_lo + hi * x_hi`
+fn fold(points: &[ProjectivePoint], x_lo: &Scalar, x_hi: &Scalar) -> Vec<ProjectivePoint> {
+    let (lo, hi) = points.split_at(points.len() / 2);
+    lo.iter()
+        .zip(hi)
+        .map(|(p, q)| *p * x_lo + *q * x_hi)
+        .collect()
+}
+
+/// proves that every value fits in `bits` bits, and returns the proof and the
+/// commitments of the values under their blinding factors.
+/// The number of values and bits must be powers of two, with at most 64 bits,
+/// and the generators must hold one base per bit.
+pub fn prove(
+    transcript: &mut Transcript,
+    generators: &Generators,
+    values: &[u64],
+    blindings: &[Scalar],
+    bits: usize,
+) -> (Proof, Vec<ProjectivePoint>) {
+    assert_eq!(values.len(), blindings.len());
+    let n = length(generators, values.len(), bits).expect("unsupported proof size");
+    let commitments: Vec<ProjectivePoint> = values
+        .iter()
+        .zip(blindings)
+        .map(|(v, r)| commit(&Scalar::from(*v), r))
+        .collect();
+    let random =
+        |len: usize| -> Vec<Scalar> { (0..len).map(|_| Scalar::random(&mut OsRng)).collect() };
+
+    // a value out of range loses its high bits, and its proof does not verify
+    let a_l: Vec<Scalar> = values
+        .iter()
+        .flat_map(|v| (0..bits).map(move |i| Scalar::from((v >> i) & 1)))
+        .collect();
+    let a_r: Vec<Scalar> = a_l.iter().map(|bit| bit - &Scalar::ONE).collect();
+    let (s_l, s_r) = (random(n), random(n));
+    let (alpha, rho) = (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+    let a = generators.commit(&a_l, &a_r, &alpha);
+    let s = generators.commit(&s_l, &s_r, &rho);
+    let (y, z) = start(transcript, bits, &commitments, &a, &s);
+
+    // l(X) = a_L - z + s_L X and r(X) = y^i (a_R + z + s_R X) + z^(2+j) 2^k
+    let ys = powers(&y, n);
+    let zs = powers(&z, values.len() + 2)[2..].to_vec();
+    let twos = powers(&Scalar::from(2u64), bits);
+    let l0: Vec<Scalar> = a_l.iter().map(|bit| bit - &z).collect();
+    let r0: Vec<Scalar> = (0..n)
+        .map(|i| ys[i] * (a_r[i] + z) + zs[i / bits] * twos[i % bits])
+        .collect();
+    let r1: Vec<Scalar> = (0..n).map(|i| ys[i] * s_r[i]).collect();
+    let (t1, t2) = (inner(&l0, &r1) + inner(&s_l, &r0), inner(&s_l, &r1));
+    let (tau1, tau2) = (Scalar::random(&mut OsRng), Scalar::random(&mut OsRng));
+    let (t1, t2) = (commit(&t1, &tau1), commit(&t2, &tau2));
+    transcript.append_point(b"t1", &t1);
+    transcript.append_point(b"t2", &t2);
+    let x = transcript.challenge(b"x");
+
+    let mut l: Vec<Scalar> = l0.iter().zip(&s_l).map(|(c, s)| *c + *s * x).collect();
+    let mut r: Vec<Scalar> = r0.iter().zip(&r1).map(|(c, s)| *c + *s * x).collect();
+    let t = inner(&l, &r);
+    let tau = tau2 * x.square() + tau1 * x + inner(&zs, blindings);
+    let mu = alpha + rho * x;
+    transcript.append_scalar(b"tau", &tau);
+    transcript.append_scalar(b"mu", &mu);
+    transcript.append_scalar(b"t", &t);
+    let u = ProjectivePoint::GENERATOR * transcript.challenge(b"w");
+
+    // inner product argument on the bases G and y^-i H
+    let y_inverse = y.invert().unwrap();
+    let mut g = generators.g[..n].to_vec();
+    let mut h: Vec<ProjectivePoint> = generators.h[..n]
+        .iter()
+        .zip(powers(&y_inverse, n))
+        .map(|(h, y)| *h * y)
+        .collect();
+    let (mut left, mut right) = (vec![], vec![]);
+    while l.len() > 1 {
+        let half = l.len() / 2;
+        let (l_lo, l_hi) = l.split_at(half);
+        let (r_lo, r_hi) = r.split_at(half);
+        let (g_lo, g_hi) = g.split_at(half);
+        let (h_lo, h_hi) = h.split_at(half);
+        let cross_l = multiply(l_lo, g_hi) + multiply(r_hi, h_lo) + u * inner(l_lo, r_hi);
+        let cross_r = multiply(l_hi, g_lo) + multiply(r_lo, h_hi) + u * inner(l_hi, r_lo);
+        transcript.append_point(b"left", &cross_l);
+        transcript.append_point(b"right", &cross_r);
+        let x = transcript.challenge(b"fold");
+        let x_inverse = x.invert().unwrap();
+        l = (0..half)
+            .map(|i| l_lo[i] * x + l_hi[i] * x_inverse)
+            .collect();
+        r = (0..half)
+            .map(|i| r_lo[i] * x_inverse + r_hi[i] * x)
+            .collect();
+        g = fold(&g, &x_inverse, &x);
+        h = fold(&h, &x, &x_inverse);
+        left.push(cross_l);
+        right.push(cross_r);
+    }
+    let proof = Proof {
+        a,
+        s,
+        t1,
+        t2,
+        tau,
+        mu,
+        t,
+        left,
+        right,
+        l: l[0],
+        r: r[0],
+    };
+    (proof, commitments)
+}
+
+/// verifies that every commitment holds a value of `bits` bits
+pub fn verify(
+    transcript: &mut Transcript,
+    generators: &Generators,
+    commitments: &[ProjectivePoint],
+    bits: usize,
+    proof: &Proof,
+) -> bool {
+    let n = match length(generators, commitments.len(), bits) {
+        Some(n) => n,
+        None => return false,
+    };
+    let rounds = n.trailing_zeros() as usize;
+    if proof.left.len() != rounds || proof.right.len() != rounds {
+        return false;
+    }
+    let (y, z) = start(transcript, bits, commitments, &proof.a, &proof.s);
+    transcript.append_point(b"t1", &proof.t1);
+    transcript.append_point(b"t2", &proof.t2);
+    let x = transcript.challenge(b"x");
+    transcript.append_scalar(b"tau", &proof.tau);
+    transcript.append_scalar(b"mu", &proof.mu);
+    transcript.append_scalar(b"t", &proof.t);
+    let u = ProjectivePoint::GENERATOR * transcript.challenge(b"w");
+
+    // t(x) commits to z^2 v + delta(y, z) + t1 x + t2 x^2
+    let ys = powers(&y, n);
+    let zs = powers(&z, commitments.len() + 2)[2..].to_vec();
+    let twos = powers(&Scalar::from(2u64), bits);
+    let delta = (z - z.square()) * ys.iter().sum::<Scalar>()
+        - z * zs.iter().sum::<Scalar>() * twos.iter().sum::<Scalar>();
+    let expected = multiply(&zs, commitments)
+        + ProjectivePoint::GENERATOR * delta
+        + proof.t1 * x
+        + proof.t2 * x.square();
+    if commit(&proof.t, &proof.tau) != expected {
+        return false;
+    }
+
+    // A + x S - z G + (z y^i + z^(2+j) 2^k) H' - mu H + t u commits to l and r
+    let y_inverse = y.invert().unwrap();
+    let mut g = generators.g[..n].to_vec();
+    let mut h: Vec<ProjectivePoint> = generators.h[..n]
+        .iter()
+        .zip(powers(&y_inverse, n))
+        .map(|(h, y)| *h * y)
+        .collect();
+    let exponents: Vec<Scalar> = (0..n)
+        .map(|i| z * ys[i] + zs[i / bits] * twos[i % bits])
+        .collect();
+    let mut p = proof.a + proof.s * x - g.iter().sum::<ProjectivePoint>() * z
+        + multiply(&exponents, &h)
+        - blinding_base() * proof.mu
+        + u * proof.t;
+    for (cross_l, cross_r) in proof.left.iter().zip(&proof.right) {
+        transcript.append_point(b"left", cross_l);
+        transcript.append_point(b"right", cross_r);
+        let x = transcript.challenge(b"fold");
+        let x_inverse = x.invert().unwrap();
+        p += *cross_l * x.square() + *cross_r * x_inverse.square();
+        g = fold(&g, &x_inverse, &x);
+        h = fold(&h, &x, &x_inverse);
+    }
+    p == g[0] * proof.l + h[0] * proof.r + u * (proof.l * proof.r)
+}
+
+/// stores the compressed points `a, s, t1, t2`, the big-endian scalars
+/// `tau, mu, t, l, r`, and the cross terms in pairs
+impl StoreValue for Proof {
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = [self.a, self.s, self.t1, self.t2]
+            .iter()
+            .flat_map(encode_point)
+            .collect();
+        for scalar in [self.tau, self.mu, self.t, self.l, self.r] {
+            bytes.extend(scalar.to_bytes());
+        }
+        for (cross_l, cross_r) in self.left.iter().zip(&self.right) {
+            bytes.extend(encode_point(cross_l));
+            bytes.extend(encode_point(cross_r));
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.len() < 292 || !(bytes.len() - 292).is_multiple_of(66) {
+            return Err(ParseError);
+        }
+        let point = |i: usize| decode_point(&bytes[i * 33..(i + 1) * 33]).ok_or(ParseError);
+        let scalar = |i: usize| decode_scalar(&bytes[132 + i * 32..164 + i * 32]).ok_or(ParseError);
+        let (mut left, mut right) = (vec![], vec![]);
+        for pair in bytes[292..].chunks_exact(66) {
+            left.push(decode_point(&pair[..33]).ok_or(ParseError)?);
+            right.push(decode_point(&pair[33..]).ok_or(ParseError)?);
+        }
+        Ok(Self {
+            a: point(0)?,
+            s: point(1)?,
+            t1: point(2)?,
+            t2: point(3)?,
+            tau: scalar(0)?,
+            mu: scalar(1)?,
+            t: scalar(2)?,
+            l: scalar(3)?,
+            r: scalar(4)?,
+            left,
+            right,
+        })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    let generators = Generators::new(b"range", 64);
+    let blindings: Vec<Scalar> = (0..2).map(|_| Scalar::random(&mut OsRng)).collect();
+    let check = |label: &[u8], commitments: &[ProjectivePoint], bits: usize, proof: &Proof| {
+        verify(
+            &mut Transcript::new(label),
+            &generators,
+            commitments,
+            bits,
+            proof,
+        )
+    };
+
+    let (proof, commitments) = prove(
+        &mut Transcript::new(b"range"),
+        &generators,
+        &[u64::MAX],
+        &blindings[..1],
+        64,
+    );
+    assert_eq!(
+        commitments[0],
+        commit(&Scalar::from(u64::MAX), &blindings[0])
+    );
+    assert!(check(b"range", &commitments, 64, &proof));
+    assert!(!check(b"other", &commitments, 64, &proof));
+    assert!(!check(b"range", &commitments, 32, &proof));
+    let bytes = proof.clone().to_bytes();
+    assert_eq!(292 + 6 * 66, bytes.len());
+    assert_eq!(proof, Proof::from_bytes(&bytes).unwrap());
+
+    // an aggregated proof covers all commitments at once
+    let (proof, commitments) = prove(
+        &mut Transcript::new(b"range"),
+        &generators,
+        &[255, 3],
+        &blindings,
+        8,
+    );
+    assert!(check(b"range", &commitments, 8, &proof));
+    assert!(!check(
+        b"range",
+        &[commitments[1], commitments[0]],
+        8,
+        &proof
+    ));
+    let mut forged = proof.clone();
+    forged.t += Scalar::ONE;
+    assert!(!check(b"range", &commitments, 8, &forged));
+    let mut forged = proof;
+    forged.l += Scalar::ONE;
+    assert!(!check(b"range", &commitments, 8, &forged));
+
+    let (proof, commitments) = prove(
+        &mut Transcript::new(b"range"),
+        &generators,
+        &[256, 3],
+        &blindings,
+        8,
+    );
+    assert!(!check(b"range", &commitments, 8, &proof));
+}
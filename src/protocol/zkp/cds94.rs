@@ -0,0 +1,148 @@
+//! implements OR-composition of Schnorr proofs
+//! Proofs of Partial Knowledge and Simplified Design of Witness Hiding Protocols
+//! https://link.springer.com/chapter/10.1007/3-540-48658-5_19
+//! The prover knows the logarithm of one of several statements `Y_j = x_j * B_j`.
+//! It simulates the other statements with challenges of its choice, and the
+//! challenge of the real one is fixed by all challenges summing to the
+//! Fiat-Shamir challenge, so that the proof hides which statement is known.
+
+use super::Transcript;
+use crate::math::weierstrass::decode_scalar;
+use crate::store::{ParseError, StoreValue};
+use k256::{
+    elliptic_curve::{rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+
+/// challenge and response of every statement, from which the commitments follow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub challenges: Vec<Scalar>,
+    pub responses: Vec<Scalar>,
+}
+
+/// absorbs the statements and commitments, and derives the challenge
+fn challenge(
+    transcript: &mut Transcript,
+    statements: &[(ProjectivePoint, ProjectivePoint)],
+    commitments: &[ProjectivePoint],
+) -> Scalar {
+    transcript.append(b"proof", b"or");
+    transcript.append(b"count", &(statements.len() as u64).to_le_bytes());
+    for ((base, public), commitment) in statements.iter().zip(commitments) {
+        transcript.append_point(b"base", base);
+        transcript.append_point(b"public", public);
+        transcript.append_point(b"commitment", commitment);
+    }
+    transcript.challenge(b"challenge")
+}
+
+/// proves knowledge of the logarithm of one statement of pairs `(base, public)`,
+/// where `secret` is the logarithm of the statement at `index`
+pub fn prove(
+    transcript: &mut Transcript,
+    statements: &[(ProjectivePoint, ProjectivePoint)],
+    index: usize,
+    secret: &Scalar,
+) -> Proof {
+    assert!(index < statements.len(), "index out of range");
+    let mut challenges: Vec<Scalar> = (0..statements.len())
+        .map(|_| Scalar::random(&mut OsRng))
+        .collect();
+    let mut responses: Vec<Scalar> = (0..statements.len())
+        .map(|_| Scalar::random(&mut OsRng))
+        .collect();
+    let nonce = Scalar::random(&mut OsRng);
+    let commitments: Vec<ProjectivePoint> = statements
+        .iter()
+        .enumerate()
+        .map(|(j, (base, public))| {
+            if j == index {
+                *base * nonce
+            } else {
+                *base * responses[j] - *public * challenges[j]
+            }
+        })
+        .collect();
+    let c = challenge(transcript, statements, &commitments);
+    challenges[index] = Scalar::ZERO;
+    challenges[index] = c - challenges.iter().sum::<Scalar>();
+    responses[index] = nonce + challenges[index] * secret;
+    Proof {
+        challenges,
+        responses,
+    }
+}
+
+pub fn verify(
+    transcript: &mut Transcript,
+    statements: &[(ProjectivePoint, ProjectivePoint)],
+    proof: &Proof,
+) -> bool {
+    if proof.challenges.len() != statements.len() || proof.responses.len() != statements.len() {
+        return false;
+    }
+    let commitments: Vec<ProjectivePoint> = statements
+        .iter()
+        .zip(proof.challenges.iter().zip(&proof.responses))
+        .map(|((base, public), (c, z))| *base * z - *public * c)
+        .collect();
+    challenge(transcript, statements, &commitments) == proof.challenges.iter().sum::<Scalar>()
+}
+
+/// stores the big-endian challenges followed by the responses
+impl StoreValue for Proof {
+    fn to_bytes(self) -> Vec<u8> {
+        self.challenges
+            .iter()
+            .chain(&self.responses)
+            .flat_map(|scalar| scalar.to_bytes())
+            .collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if !bytes.len().is_multiple_of(64) {
+            return Err(ParseError);
+        }
+        let scalars = bytes
+            .chunks_exact(32)
+            .map(|chunk| decode_scalar(chunk).ok_or(ParseError))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (challenges, responses) = scalars.split_at(scalars.len() / 2);
+        Ok(Self {
+            challenges: challenges.to_vec(),
+            responses: responses.to_vec(),
+        })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use k256::elliptic_curve::ops::MulByGenerator;
+
+    // an ElGamal ciphertext `(r G, r Y + m G)` encrypts a bit m
+    let key = ProjectivePoint::mul_by_generator(&Scalar::random(&mut OsRng));
+    let r = Scalar::random(&mut OsRng);
+    let body = key * r + ProjectivePoint::GENERATOR;
+    let ephemeral = ProjectivePoint::mul_by_generator(&r);
+    // the bit m is 0 or 1 if `ephemeral + body - m G = r (G + Y)` for one of them
+    let statements: Vec<_> = [body, body - ProjectivePoint::GENERATOR]
+        .into_iter()
+        .map(|target| {
+            let base = ProjectivePoint::GENERATOR + key;
+            (base, ephemeral + target)
+        })
+        .collect();
+    let proof = prove(&mut Transcript::new(b"bit"), &statements, 1, &r);
+    assert!(verify(&mut Transcript::new(b"bit"), &statements, &proof));
+    let forged = prove(&mut Transcript::new(b"bit"), &statements, 0, &r);
+    assert!(!verify(&mut Transcript::new(b"bit"), &statements, &forged));
+    assert!(!verify(
+        &mut Transcript::new(b"bit"),
+        &statements[..1],
+        &proof
+    ));
+    let bytes = proof.clone().to_bytes();
+    assert_eq!(128, bytes.len());
+    assert_eq!(proof, Proof::from_bytes(&bytes).unwrap());
+}
@@ -0,0 +1,108 @@
+//! implements Chaum-Pedersen proofs of equality of discrete logarithms
+//! Wallet Databases with Observers
+//! https://link.springer.com/chapter/10.1007/3-540-48071-4_7
+//! The prover of `Y1 = x * B1` and `Y2 = x * B2` runs the Schnorr protocol on
+//! both bases with one nonce and one response. Proving that a DH value or a
+//! partial decryption `x * E` matches the public key `x * G` is the typical use.
+
+use super::Transcript;
+use crate::math::weierstrass::{decode_point, decode_scalar, encode_point};
+use crate::store::{ParseError, StoreValue};
+use k256::{
+    elliptic_curve::{rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proof {
+    pub commitments: [ProjectivePoint; 2],
+    pub response: Scalar,
+}
+
+/// absorbs both statements and commitments, and derives the challenge
+fn challenge(
+    transcript: &mut Transcript,
+    bases: &[ProjectivePoint; 2],
+    publics: &[ProjectivePoint; 2],
+    commitments: &[ProjectivePoint; 2],
+) -> Scalar {
+    transcript.append(b"proof", b"chaum-pedersen");
+    for i in 0..2 {
+        transcript.append_point(b"base", &bases[i]);
+        transcript.append_point(b"public", &publics[i]);
+        transcript.append_point(b"commitment", &commitments[i]);
+    }
+    transcript.challenge(b"challenge")
+}
+
+/// proves that `secret * bases[0]` and `secret * bases[1]` share the logarithm `secret`
+pub fn prove(transcript: &mut Transcript, bases: &[ProjectivePoint; 2], secret: &Scalar) -> Proof {
+    let nonce = Scalar::random(&mut OsRng);
+    let commitments = bases.map(|base| base * nonce);
+    let publics = bases.map(|base| base * secret);
+    let c = challenge(transcript, bases, &publics, &commitments);
+    Proof {
+        commitments,
+        response: nonce + c * secret,
+    }
+}
+
+pub fn verify(
+    transcript: &mut Transcript,
+    bases: &[ProjectivePoint; 2],
+    publics: &[ProjectivePoint; 2],
+    proof: &Proof,
+) -> bool {
+    let c = challenge(transcript, bases, publics, &proof.commitments);
+    (0..2).all(|i| bases[i] * proof.response == proof.commitments[i] + publics[i] * c)
+}
+
+/// stores both compressed commitments followed by the big-endian response
+impl StoreValue for Proof {
+    fn to_bytes(self) -> Vec<u8> {
+        [
+            encode_point(&self.commitments[0]),
+            encode_point(&self.commitments[1]),
+            self.response.to_bytes().to_vec(),
+        ]
+        .concat()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.len() != 98 {
+            return Err(ParseError);
+        }
+        Ok(Self {
+            commitments: [
+                decode_point(&bytes[..33]).ok_or(ParseError)?,
+                decode_point(&bytes[33..66]).ok_or(ParseError)?,
+            ],
+            response: decode_scalar(&bytes[66..]).ok_or(ParseError)?,
+        })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use crate::math::weierstrass::hash_to_curve;
+
+    // a partial decryption of an ephemeral key matches the public key share
+    let secret = Scalar::random(&mut OsRng);
+    let bases = [ProjectivePoint::GENERATOR, hash_to_curve(b"ephemeral")];
+    let publics = bases.map(|base| base * secret);
+    let proof = prove(&mut Transcript::new(b"decryption"), &bases, &secret);
+    assert!(verify(
+        &mut Transcript::new(b"decryption"),
+        &bases,
+        &publics,
+        &proof
+    ));
+    let wrong = [publics[0], bases[1] * (secret + Scalar::ONE)];
+    assert!(!verify(
+        &mut Transcript::new(b"decryption"),
+        &bases,
+        &wrong,
+        &proof
+    ));
+    assert_eq!(proof, Proof::from_bytes(&proof.to_bytes()).unwrap());
+}
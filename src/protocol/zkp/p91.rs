@@ -0,0 +1,89 @@
+//! implements Pedersen commitments to scalars and vectors on secp256k1
+//! Non-Interactive and Information-Theoretic Secure Verifiable Secret Sharing
+//! https://link.springer.com/chapter/10.1007/3-540-46766-1_9
+//! A commitment `v * G + r * H` hides v perfectly and binds it as long as the
+//! logarithm of H is unknown, so all bases besides G are hashed to the curve.
+//! Vector commitments use one base per entry.
+
+use crate::math::weierstrass::hash_to_curve;
+use k256::{ProjectivePoint, Scalar};
+
+/// base of the blinding factors
+pub fn blinding_base() -> ProjectivePoint {
+    hash_to_curve(b"pedersen blinding")
+}
+
+/// commits to a value with a blinding factor
+pub fn commit(value: &Scalar, blinding: &Scalar) -> ProjectivePoint {
+    ProjectivePoint::GENERATOR * value + blinding_base() * blinding
+}
+
+/// computes the inner product of scalars and points
+pub fn multiply(scalars: &[Scalar], points: &[ProjectivePoint]) -> ProjectivePoint {
+    assert_eq!(scalars.len(), points.len());
+    scalars
+        .iter()
+        .zip(points)
+        .fold(ProjectivePoint::IDENTITY, |acc, (s, p)| acc + *p * s)
+}
+
+/// independent bases of vector commitments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generators {
+    pub g: Vec<ProjectivePoint>,
+    pub h: Vec<ProjectivePoint>,
+}
+
+impl Generators {
+    /// derives two vectors of `len` bases for the application named `label`
+    pub fn new(label: &[u8], len: usize) -> Self {
+        let bases = |tag: &[u8]| {
+            (0..len as u64)
+                .map(|i| hash_to_curve(&[label, tag, &i.to_le_bytes()].concat()))
+                .collect()
+        };
+        Self {
+            g: bases(b"g"),
+            h: bases(b"h"),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.g.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.g.is_empty()
+    }
+
+    /// commits to two vectors of at most `len` entries with a blinding factor
+    pub fn commit(&self, a: &[Scalar], b: &[Scalar], blinding: &Scalar) -> ProjectivePoint {
+        multiply(a, &self.g[..a.len()])
+            + multiply(b, &self.h[..b.len()])
+            + blinding_base() * blinding
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use k256::elliptic_curve::{rand_core::OsRng, Field};
+
+    let (v, r) = (Scalar::from(42u64), Scalar::random(&mut OsRng));
+    let c = commit(&v, &r);
+    // commitments are additively homomorphic
+    let (w, s) = (Scalar::from(8u64), Scalar::random(&mut OsRng));
+    assert_eq!(commit(&(v + w), &(r + s)), c + commit(&w, &s));
+    assert_ne!(c, commit(&v, &s));
+
+    let generators = Generators::new(b"test", 4);
+    assert_eq!(generators, Generators::new(b"test", 4));
+    assert_ne!(generators.g[0], Generators::new(b"other", 4).g[0]);
+    let a: Vec<Scalar> = (1..=4u64).map(Scalar::from).collect();
+    let b: Vec<Scalar> = (5..=7u64).map(Scalar::from).collect();
+    let c = generators.commit(&a, &b, &r);
+    let doubled: Vec<Scalar> = a.iter().map(|x| x.double()).collect();
+    assert_eq!(
+        c + generators.commit(&a, &[], &s),
+        generators.commit(&doubled, &b, &(r + s))
+    );
+}
@@ -0,0 +1,104 @@
+//! implements Schnorr proofs of knowledge of a discrete logarithm
+//! Efficient Identification and Signatures for Smart Cards
+//! https://link.springer.com/chapter/10.1007/0-387-34805-0_22
+//! The prover of `Y = x * B` commits to `R = r * B` and answers the challenge
+//! `c` with `z = r + c * x`, which satisfies `z * B = R + c * Y`.
+
+use super::Transcript;
+use crate::math::weierstrass::{decode_point, decode_scalar, encode_point};
+use crate::store::{ParseError, StoreValue};
+use k256::{
+    elliptic_curve::{rand_core::OsRng, Field},
+    ProjectivePoint, Scalar,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Proof {
+    pub commitment: ProjectivePoint,
+    pub response: Scalar,
+}
+
+/// absorbs the statement and the commitment, and derives the challenge
+fn challenge(
+    transcript: &mut Transcript,
+    base: &ProjectivePoint,
+    public: &ProjectivePoint,
+    commitment: &ProjectivePoint,
+) -> Scalar {
+    transcript.append(b"proof", b"schnorr");
+    transcript.append_point(b"base", base);
+    transcript.append_point(b"public", public);
+    transcript.append_point(b"commitment", commitment);
+    transcript.challenge(b"challenge")
+}
+
+/// proves knowledge of `secret` with `public = secret * base`
+pub fn prove(transcript: &mut Transcript, base: &ProjectivePoint, secret: &Scalar) -> Proof {
+    let nonce = Scalar::random(&mut OsRng);
+    let commitment = *base * nonce;
+    let c = challenge(transcript, base, &(*base * secret), &commitment);
+    Proof {
+        commitment,
+        response: nonce + c * secret,
+    }
+}
+
+pub fn verify(
+    transcript: &mut Transcript,
+    base: &ProjectivePoint,
+    public: &ProjectivePoint,
+    proof: &Proof,
+) -> bool {
+    let c = challenge(transcript, base, public, &proof.commitment);
+    *base * proof.response == proof.commitment + *public * c
+}
+
+/// stores the compressed commitment followed by the big-endian response
+impl StoreValue for Proof {
+    fn to_bytes(self) -> Vec<u8> {
+        [
+            encode_point(&self.commitment),
+            self.response.to_bytes().to_vec(),
+        ]
+        .concat()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.len() != 65 {
+            return Err(ParseError);
+        }
+        Ok(Self {
+            commitment: decode_point(&bytes[..33]).ok_or(ParseError)?,
+            response: decode_scalar(&bytes[33..]).ok_or(ParseError)?,
+        })
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use k256::elliptic_curve::ops::MulByGenerator;
+
+    let secret = Scalar::random(&mut OsRng);
+    let public = ProjectivePoint::mul_by_generator(&secret);
+    let proof = prove(
+        &mut Transcript::new(b"test"),
+        &ProjectivePoint::GENERATOR,
+        &secret,
+    );
+    let verify_with = |label: &[u8], public: &ProjectivePoint, proof: &Proof| {
+        verify(
+            &mut Transcript::new(label),
+            &ProjectivePoint::GENERATOR,
+            public,
+            proof,
+        )
+    };
+    assert!(verify_with(b"test", &public, &proof));
+    // the proof binds to the context and the statement
+    assert!(!verify_with(b"other", &public, &proof));
+    assert!(!verify_with(b"test", &(public + public), &proof));
+    let mut forged = proof;
+    forged.response += Scalar::ONE;
+    assert!(!verify_with(b"test", &public, &forged));
+    assert_eq!(proof, Proof::from_bytes(&proof.to_bytes()).unwrap());
+}
@@ -96,6 +96,7 @@ impl<T: StoreValue> StoreValue for Vec<T> {
 }
 
 pub mod disk;
+pub mod merkle;
 
 #[cfg(feature = "duckdb")]
 pub mod duckdb;
@@ -0,0 +1,390 @@
+//! a `Store` wrapper committing to its key/value pairs with a Blake2 Merkle tree
+//! Leaves hash the pairs in key order, so that a proof of two adjacent leaves
+//! also shows that no key between them is stored. A client holding the root
+//! checks an answer with `verify`, without access to the store.
+
+use super::{ParseError, Store, StoreValue};
+use blake2::{Blake2b512, Digest};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::io::{Error as IOError, ErrorKind, Result as IOResult};
+
+pub type Hash = [u8; 32];
+
+fn hash(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Blake2b512::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+    hasher.finalize()[..32].try_into().unwrap()
+}
+
+fn leaf(key: &str, value: &[u8]) -> Hash {
+    hash(&[b"leaf", key.as_bytes(), value])
+}
+
+fn node(left: &Hash, right: &Hash) -> Hash {
+    hash(&[b"node", left, right])
+}
+
+/// root of a tree without leaves
+pub fn empty() -> Hash {
+    hash(&[b"empty"])
+}
+
+/// pairs up the nodes of a level, and promotes the last one of an odd level
+fn parents(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// authentication path of the leaf at `index` among `count` leaves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    pub index: usize,
+    pub count: usize,
+    pub siblings: Vec<Hash>,
+}
+
+impl Path {
+    /// recomputes the root from a leaf, or returns None if the path is malformed
+    fn root(&self, leaf: Hash) -> Option<Hash> {
+        if self.index >= self.count {
+            return None;
+        }
+        let (mut index, mut count, mut hash) = (self.index, self.count, leaf);
+        let mut siblings = self.siblings.iter();
+        while count > 1 {
+            if index ^ 1 < count {
+                let sibling = siblings.next()?;
+                hash = if index % 2 == 0 {
+                    node(&hash, sibling)
+                } else {
+                    node(sibling, &hash)
+                };
+            }
+            index /= 2;
+            count = count.div_ceil(2);
+        }
+        siblings.next().is_none().then_some(hash)
+    }
+}
+
+/// a neighbouring pair shown in a non-inclusion proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbour {
+    pub key: String,
+    pub value: Vec<u8>,
+    pub path: Path,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Proof {
+    /// path of the leaf of the key
+    Inclusion(Path),
+    /// the closest keys below and above the key, which are adjacent leaves
+    Exclusion {
+        lower: Option<Neighbour>,
+        upper: Option<Neighbour>,
+    },
+}
+
+/// checks that the tree of `root` holds `value` at `key`, or no value if `value` is None.
+/// Values are given in their stored bytes.
+pub fn verify(root: &Hash, key: &str, value: Option<&[u8]>, proof: &Proof) -> bool {
+    match (value, proof) {
+        (Some(value), Proof::Inclusion(path)) => path.root(leaf(key, value)) == Some(*root),
+        (None, Proof::Exclusion { lower, upper }) => {
+            let check = |neighbour: &Neighbour| {
+                neighbour.path.root(leaf(&neighbour.key, &neighbour.value)) == Some(*root)
+            };
+            match (lower, upper) {
+                (None, None) => *root == empty(),
+                (Some(lower), None) => {
+                    lower.key.as_str() < key
+                        && lower.path.index + 1 == lower.path.count
+                        && check(lower)
+                }
+                (None, Some(upper)) => {
+                    key < upper.key.as_str() && upper.path.index == 0 && check(upper)
+                }
+                (Some(lower), Some(upper)) => {
+                    lower.key.as_str() < key
+                        && key < upper.key.as_str()
+                        && lower.path.index + 1 == upper.path.index
+                        && lower.path.count == upper.path.count
+                        && check(lower)
+                        && check(upper)
+                }
+            }
+        }
+        _ => false,
+    }
+}
+
+/// wraps a store and keeps the root over the pairs set through it
+/// # Example
+/// ```
+/// use dense::store::{Store, disk::DiskStore, merkle::{verify, MerkleStore}};
+/// let mut store = MerkleStore::new(DiskStore::new("data/Alice", "merkle").unwrap());
+/// store.set("age", &42u32).unwrap();
+/// let proof = store.prove("age");
+/// assert!(verify(&store.root(), "age", Some(&42u32.to_le_bytes()), &proof));
+/// ```
+pub struct MerkleStore<S: Store> {
+    store: S,
+    pairs: BTreeMap<String, Vec<u8>>,
+    root: Hash,
+}
+
+impl<S: Store> MerkleStore<S> {
+    /// wraps a store, committing to no pairs yet
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            pairs: BTreeMap::new(),
+            root: empty(),
+        }
+    }
+
+    /// wraps a store, committing to the pairs of `keys` already in it
+    pub fn open(store: S, keys: &[String]) -> IOResult<Self> {
+        let mut merkle = Self::new(store);
+        for key in keys {
+            let value = merkle
+                .store
+                .get::<Vec<u8>>(key)
+                .map_err(|_| IOError::new(ErrorKind::NotFound, "key not found"))?;
+            merkle.pairs.insert(key.clone(), value);
+        }
+        merkle.update();
+        Ok(merkle)
+    }
+
+    pub fn root(&self) -> Hash {
+        self.root
+    }
+
+    /// returns the inner store
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    fn leaves(&self) -> Vec<Hash> {
+        self.pairs
+            .iter()
+            .map(|(key, value)| leaf(key, value))
+            .collect()
+    }
+
+    fn update(&mut self) {
+        let mut level = self.leaves();
+        if level.is_empty() {
+            self.root = empty();
+            return;
+        }
+        while level.len() > 1 {
+            level = parents(&level);
+        }
+        self.root = level[0];
+    }
+
+    fn path(&self, leaves: &[Hash], index: usize) -> Path {
+        let mut siblings = vec![];
+        let (mut level, mut i) = (leaves.to_vec(), index);
+        while level.len() > 1 {
+            if let Some(sibling) = level.get(i ^ 1) {
+                siblings.push(*sibling);
+            }
+            level = parents(&level);
+            i /= 2;
+        }
+        Path {
+            index,
+            count: leaves.len(),
+            siblings,
+        }
+    }
+
+    /// proves that `key` holds its current value, or that it is not stored
+    pub fn prove(&self, key: &str) -> Proof {
+        let leaves = self.leaves();
+        let neighbour = |index: usize| {
+            let (key, value) = self.pairs.iter().nth(index).unwrap();
+            Neighbour {
+                key: key.clone(),
+                value: value.clone(),
+                path: self.path(&leaves, index),
+            }
+        };
+        let below = self.pairs.keys().take_while(|k| k.as_str() < key).count();
+        if self.pairs.contains_key(key) {
+            Proof::Inclusion(self.path(&leaves, below))
+        } else {
+            Proof::Exclusion {
+                lower: (below > 0).then(|| neighbour(below - 1)),
+                upper: (below < leaves.len()).then(|| neighbour(below)),
+            }
+        }
+    }
+}
+
+impl<S: Store> Store for MerkleStore<S> {
+    fn get<T: StoreValue>(&self, key: &str) -> Result<T, impl Error> {
+        self.store.get(key)
+    }
+
+    fn set(&mut self, key: &str, value: &impl StoreValue) -> Result<(), impl Error> {
+        if let Err(e) = self.store.set(key, value) {
+            return Err(IOError::other(e.to_string()));
+        }
+        self.pairs.insert(key.to_string(), value.clone().to_bytes());
+        self.update();
+        IOResult::Ok(())
+    }
+}
+
+fn put_bytes(bytes: &mut Vec<u8>, part: &[u8]) {
+    bytes.extend((part.len() as u32).to_le_bytes());
+    bytes.extend(part);
+}
+
+fn take_bytes<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], ParseError> {
+    let len = bytes.get(..4).ok_or(ParseError)?;
+    let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+    let part = bytes.get(4..4 + len).ok_or(ParseError)?;
+    *bytes = &bytes[4 + len..];
+    Ok(part)
+}
+
+impl Path {
+    fn put(&self, bytes: &mut Vec<u8>) {
+        bytes.extend((self.index as u64).to_le_bytes());
+        bytes.extend((self.count as u64).to_le_bytes());
+        put_bytes(bytes, &self.siblings.concat());
+    }
+
+    fn take(bytes: &mut &[u8]) -> Result<Self, ParseError> {
+        let mut word = || -> Result<usize, ParseError> {
+            let word = bytes.get(..8).ok_or(ParseError)?;
+            let word = u64::from_le_bytes(word.try_into().unwrap());
+            *bytes = &bytes[8..];
+            usize::try_from(word).map_err(|_| ParseError)
+        };
+        let (index, count) = (word()?, word()?);
+        let siblings = take_bytes(bytes)?;
+        if siblings.len() % 32 != 0 {
+            return Err(ParseError);
+        }
+        Ok(Self {
+            index,
+            count,
+            siblings: siblings
+                .chunks_exact(32)
+                .map(|chunk| chunk.try_into().unwrap())
+                .collect(),
+        })
+    }
+}
+
+/// stores a tag byte, then the paths and neighbours with length-prefixed fields
+impl StoreValue for Proof {
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = vec![];
+        match self {
+            Proof::Inclusion(path) => {
+                bytes.push(0);
+                path.put(&mut bytes);
+            }
+            Proof::Exclusion { lower, upper } => {
+                bytes.push(1);
+                for neighbour in [lower, upper] {
+                    match neighbour {
+                        Some(neighbour) => {
+                            bytes.push(1);
+                            put_bytes(&mut bytes, neighbour.key.as_bytes());
+                            put_bytes(&mut bytes, &neighbour.value);
+                            neighbour.path.put(&mut bytes);
+                        }
+                        None => bytes.push(0),
+                    }
+                }
+            }
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        let (tag, mut bytes) = bytes.split_first().ok_or(ParseError)?;
+        let proof = match tag {
+            0 => Proof::Inclusion(Path::take(&mut bytes)?),
+            1 => {
+                let mut neighbour = || -> Result<Option<Neighbour>, ParseError> {
+                    let (present, rest) = bytes.split_first().ok_or(ParseError)?;
+                    bytes = rest;
+                    match present {
+                        0 => Ok(None),
+                        1 => Ok(Some(Neighbour {
+                            key: String::from_bytes(take_bytes(&mut bytes)?)?,
+                            value: take_bytes(&mut bytes)?.to_vec(),
+                            path: Path::take(&mut bytes)?,
+                        })),
+                        _ => Err(ParseError),
+                    }
+                };
+                let lower = neighbour()?;
+                let upper = neighbour()?;
+                Proof::Exclusion { lower, upper }
+            }
+            _ => return Err(ParseError),
+        };
+        if !bytes.is_empty() {
+            return Err(ParseError);
+        }
+        Ok(proof)
+    }
+}
+
+#[test]
+fn test_correctness() {
+    use super::disk::DiskStore;
+
+    let mut store = MerkleStore::new(DiskStore::new("data/Alice", "ledger").unwrap());
+    assert_eq!(empty(), store.root());
+    assert!(verify(&store.root(), "b", None, &store.prove("b")));
+    for (key, value) in [("b", 2u64), ("d", 4), ("f", 6), ("h", 8), ("j", 10)] {
+        let root = store.root();
+        store.set(key, &value).unwrap();
+        assert_ne!(root, store.root());
+    }
+    let root = store.root();
+    for key in ["b", "d", "f", "h", "j"] {
+        let value = store.get::<u64>(key).unwrap().to_le_bytes();
+        let proof = store.prove(key);
+        assert!(verify(&root, key, Some(&value), &proof));
+        assert!(!verify(&root, key, Some(&7u64.to_le_bytes()), &proof));
+        assert!(!verify(&root, key, None, &proof));
+        assert_eq!(proof, Proof::from_bytes(&proof.clone().to_bytes()).unwrap());
+    }
+    for key in ["a", "c", "g", "i", "k"] {
+        let proof = store.prove(key);
+        assert!(verify(&root, key, None, &proof));
+        assert!(!verify(&root, key, Some(&[]), &proof));
+        assert_eq!(proof, Proof::from_bytes(&proof.clone().to_bytes()).unwrap());
+    }
+    // neighbours of another key do not prove absence
+    assert!(!verify(&root, "e", None, &store.prove("c")));
+    assert!(!verify(&root, "d", None, &store.prove("c")));
+
+    // reopening the store restores the root
+    let keys: Vec<String> = ["b", "d", "f", "h", "j"].map(String::from).to_vec();
+    let store = MerkleStore::open(store.into_inner(), &keys).unwrap();
+    assert_eq!(root, store.root());
+}